@@ -4,7 +4,10 @@ use async_trait::async_trait;
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 
-use crate::common::{ChatMessage, ChatResponse};
+use crate::common::{
+    ChatMessage, ChatResponse, ChatStream, ToolChatResponse, ToolChatStream, ToolDefinition,
+    ToolStreamEvent,
+};
 
 static DEFAULT_CLIENT: Lazy<Arc<reqwest::Client>> = Lazy::new(|| Arc::new(reqwest::Client::new()));
 
@@ -18,7 +21,7 @@ pub struct BrainParameters {
 }
 
 #[async_trait]
-pub trait Brain {
+pub trait Brain: Debug {
     fn api_key(&self, debug: bool) -> String;
 
     fn default_parameters(&self) -> BrainParameters {
@@ -40,4 +43,47 @@ pub trait Brain {
         params: BrainParameters,
         messages: Vec<ChatMessage>,
     ) -> anyhow::Result<ChatResponse>;
+
+    /// Like `prompt_chat`, but yields the text while it's being generated.
+    /// Brains without streaming support yield the whole response at once.
+    async fn prompt_chat_stream(
+        &self,
+        params: BrainParameters,
+        messages: Vec<ChatMessage>,
+    ) -> anyhow::Result<ChatStream> {
+        let response = self.prompt_chat(params, messages).await?;
+
+        Ok(Box::pin(futures_util::stream::once(async move {
+            Ok(response.message.content)
+        })))
+    }
+
+    fn supports_tools(&self) -> bool {
+        false
+    }
+
+    /// Like `prompt_chat`, but the model answers by calling one of the given tools
+    async fn prompt_tools(
+        &self,
+        _params: BrainParameters,
+        _messages: Vec<ChatMessage>,
+        _tools: Vec<ToolDefinition>,
+    ) -> anyhow::Result<ToolChatResponse> {
+        anyhow::bail!("This brain doesn't support tool calling")
+    }
+
+    /// Like `prompt_tools`, but yields the tool input while it's being generated.
+    /// Brains without streaming support yield only the complete response.
+    async fn prompt_tools_stream(
+        &self,
+        params: BrainParameters,
+        messages: Vec<ChatMessage>,
+        tools: Vec<ToolDefinition>,
+    ) -> anyhow::Result<ToolChatStream> {
+        let response = self.prompt_tools(params, messages, tools).await?;
+
+        Ok(Box::pin(futures_util::stream::once(async move {
+            Ok(ToolStreamEvent::Done(response))
+        })))
+    }
 }
@@ -1,10 +1,13 @@
 use async_trait::async_trait;
+use futures_util::StreamExt;
 use hex_common::load_image_from_url;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 use crate::{
     brain::{Brain, BrainParameters},
     common::*,
+    stream::response_lines,
     util::remove_italic_actions,
 };
 
@@ -45,30 +48,135 @@ pub struct ClaudeRequest {
     pub messages: Vec<ClaudeChatMessage>,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct ClaudeBrain;
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClaudeTool {
+    pub name: String,
+    pub description: String,
+    pub input_schema: Value,
+}
 
-#[async_trait]
-impl Brain for ClaudeBrain {
-    fn api_key(&self, _debug: bool) -> String {
-        std::env::var("CLAUDE_API_KEY").expect("Expected a valid Claude API key")
-    }
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClaudeToolRequest {
+    #[serde(flatten)]
+    pub request: ClaudeRequest,
+    pub tools: Vec<ClaudeTool>,
+    pub tool_choice: Value,
+    pub stream: bool,
+}
 
-    fn default_parameters(&self) -> BrainParameters {
-        BrainParameters {
-            debug: true,
-            model: "claude-3-haiku-20240307".to_string(),
-            max_tokens: 400,
-            system_prompt: String::new(),
-            strip_italic_actions: true,
-        }
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClaudeResponseBlock {
+    Text {
+        text: String,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        input: Value,
+    },
+    /// Blocks we don't use, like `thinking`
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct ClaudeToolResponse {
+    pub content: Vec<ClaudeResponseBlock>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct ClaudeStreamRequest {
+    #[serde(flatten)]
+    pub request: ClaudeRequest,
+    pub stream: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClaudeStreamDelta {
+    TextDelta {
+        text: String,
+    },
+    /// A piece of the input of a tool call
+    InputJsonDelta {
+        partial_json: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+/// A content block starting in a stream. Its content comes in the deltas that follow
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClaudeStreamBlock {
+    ToolUse {
+        name: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+/// Server-sent event of the messages API. Only blocks, deltas and errors matter to us.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClaudeStreamEvent {
+    ContentBlockStart {
+        content_block: ClaudeStreamBlock,
+    },
+    ContentBlockDelta {
+        delta: ClaudeStreamDelta,
+    },
+    Error {
+        error: Value,
+    },
+    #[serde(other)]
+    Other,
+}
+
+/// Text and tool calls of a streamed response, assembled from its deltas
+#[derive(Debug, Clone, Default)]
+struct StreamedToolResponse {
+    text: String,
+    /// Name and JSON input of each tool call
+    tool_calls: Vec<(String, String)>,
+}
+
+impl StreamedToolResponse {
+    fn finish(&self) -> anyhow::Result<ToolChatResponse> {
+        let tool_calls = self
+            .tool_calls
+            .iter()
+            .map(|(name, input)| {
+                Ok(ToolCall {
+                    name: name.clone(),
+                    input: match input.trim() {
+                        "" => Value::Object(Default::default()),
+                        input => serde_json::from_str(input)?,
+                    },
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        Ok(ToolChatResponse {
+            message: ChatMessage {
+                role: Role::Assistant,
+                content: self.text.trim().to_owned(),
+                image_url: None,
+            },
+            tool_calls,
+        })
     }
+}
 
-    async fn prompt_chat(
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ClaudeBrain;
+
+impl ClaudeBrain {
+    async fn create_messages(
         &self,
-        params: BrainParameters,
         mut messages: Vec<ChatMessage>,
-    ) -> anyhow::Result<ChatResponse> {
+    ) -> anyhow::Result<Vec<ClaudeChatMessage>> {
         let mut claude_messages = Vec::with_capacity(messages.len());
         let len = messages.len();
 
@@ -116,20 +224,21 @@ impl Brain for ClaudeBrain {
             claude_messages.push(claude_message);
         }
 
-        let request = ClaudeRequest {
-            model: params.model,
-            max_tokens: params.max_tokens,
-            messages: claude_messages,
-            system: params.system_prompt.to_owned(),
-        };
+        Ok(claude_messages)
+    }
 
+    async fn send_request(
+        &self,
+        debug: bool,
+        request: &impl Serialize,
+    ) -> anyhow::Result<reqwest::Response> {
         let response = self
             .http_client()
             .post("https://api.anthropic.com/v1/messages")
-            .header("x-api-key", self.api_key(params.debug))
+            .header("x-api-key", self.api_key(debug))
             .header("content-type", "application/json")
             .header("anthropic-version", "2023-06-01")
-            .json(&request)
+            .json(request)
             .send()
             .await?;
 
@@ -140,6 +249,68 @@ impl Brain for ClaudeBrain {
             return Err(anyhow::anyhow!("Status code: {}\n{:?}", status, text));
         }
 
+        Ok(response)
+    }
+
+    async fn tool_request(
+        &self,
+        params: BrainParameters,
+        messages: Vec<ChatMessage>,
+        tools: Vec<ToolDefinition>,
+        stream: bool,
+    ) -> anyhow::Result<ClaudeToolRequest> {
+        Ok(ClaudeToolRequest {
+            request: ClaudeRequest {
+                model: params.model,
+                max_tokens: params.max_tokens,
+                messages: self.create_messages(messages).await?,
+                system: params.system_prompt,
+            },
+            tools: tools
+                .into_iter()
+                .map(|tool| ClaudeTool {
+                    name: tool.name,
+                    description: tool.description,
+                    input_schema: tool.input_schema,
+                })
+                .collect(),
+            // Forces the model to call one of the tools instead of answering with text
+            tool_choice: serde_json::json!({ "type": "any" }),
+            stream,
+        })
+    }
+}
+
+#[async_trait]
+impl Brain for ClaudeBrain {
+    fn api_key(&self, _debug: bool) -> String {
+        std::env::var("CLAUDE_API_KEY").expect("Expected a valid Claude API key")
+    }
+
+    fn default_parameters(&self) -> BrainParameters {
+        BrainParameters {
+            debug: true,
+            model: "claude-3-haiku-20240307".to_string(),
+            max_tokens: 400,
+            system_prompt: String::new(),
+            strip_italic_actions: true,
+        }
+    }
+
+    async fn prompt_chat(
+        &self,
+        params: BrainParameters,
+        messages: Vec<ChatMessage>,
+    ) -> anyhow::Result<ChatResponse> {
+        let request = ClaudeRequest {
+            model: params.model,
+            max_tokens: params.max_tokens,
+            messages: self.create_messages(messages).await?,
+            system: params.system_prompt.to_owned(),
+        };
+
+        let response = self.send_request(params.debug, &request).await?;
+
         let mut response: ClaudeChatResponse = response.json().await?;
 
         if params.strip_italic_actions {
@@ -175,4 +346,169 @@ impl Brain for ClaudeBrain {
             },
         })
     }
+
+    async fn prompt_chat_stream(
+        &self,
+        params: BrainParameters,
+        messages: Vec<ChatMessage>,
+    ) -> anyhow::Result<ChatStream> {
+        let request = ClaudeStreamRequest {
+            request: ClaudeRequest {
+                model: params.model,
+                max_tokens: params.max_tokens,
+                messages: self.create_messages(messages).await?,
+                system: params.system_prompt.to_owned(),
+            },
+            stream: true,
+        };
+
+        let response = self.send_request(params.debug, &request).await?;
+
+        let deltas = response_lines(response).filter_map(|line| async move {
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => return Some(Err(e)),
+            };
+            let data = line.strip_prefix("data:")?.trim();
+
+            match serde_json::from_str(data) {
+                Ok(ClaudeStreamEvent::ContentBlockDelta {
+                    delta: ClaudeStreamDelta::TextDelta { text },
+                }) => Some(Ok(text)),
+                Ok(ClaudeStreamEvent::Error { error }) => {
+                    Some(Err(anyhow::anyhow!("Stream error: {error}")))
+                }
+                Ok(_) => None,
+                Err(e) => Some(Err(e.into())),
+            }
+        });
+
+        Ok(Box::pin(deltas))
+    }
+
+    fn supports_tools(&self) -> bool {
+        true
+    }
+
+    async fn prompt_tools(
+        &self,
+        params: BrainParameters,
+        messages: Vec<ChatMessage>,
+        tools: Vec<ToolDefinition>,
+    ) -> anyhow::Result<ToolChatResponse> {
+        let request = self
+            .tool_request(params.clone(), messages, tools, false)
+            .await?;
+
+        let response = self.send_request(params.debug, &request).await?;
+        let response: ClaudeToolResponse = response.json().await?;
+
+        let mut text = String::new();
+        let mut tool_calls = vec![];
+        for block in response.content {
+            match block {
+                ClaudeResponseBlock::Text { text: content } => text.push_str(&content),
+                ClaudeResponseBlock::ToolUse { name, input, .. } => {
+                    tool_calls.push(ToolCall { name, input })
+                }
+                ClaudeResponseBlock::Unknown => {}
+            }
+        }
+
+        Ok(ToolChatResponse {
+            message: ChatMessage {
+                role: Role::Assistant,
+                content: text.trim().to_owned(),
+                image_url: None,
+            },
+            tool_calls,
+        })
+    }
+
+    async fn prompt_tools_stream(
+        &self,
+        params: BrainParameters,
+        messages: Vec<ChatMessage>,
+        tools: Vec<ToolDefinition>,
+    ) -> anyhow::Result<ToolChatStream> {
+        let request = self
+            .tool_request(params.clone(), messages, tools, true)
+            .await?;
+        let response = self.send_request(params.debug, &request).await?;
+
+        let state = (
+            Box::pin(response_lines(response)),
+            StreamedToolResponse::default(),
+            false,
+        );
+        let events =
+            futures_util::stream::unfold(state, |(mut lines, mut response, finished)| async move {
+                if finished {
+                    return None;
+                }
+
+                loop {
+                    let line = match lines.next().await {
+                        Some(Ok(line)) => line,
+                        Some(Err(e)) => return Some((Err(e), (lines, response, true))),
+                        None => {
+                            let event = response.finish().map(ToolStreamEvent::Done);
+                            return Some((event, (lines, response, true)));
+                        }
+                    };
+                    let Some(data) = line.strip_prefix("data:") else {
+                        continue;
+                    };
+
+                    match serde_json::from_str(data.trim()) {
+                        Ok(ClaudeStreamEvent::ContentBlockStart {
+                            content_block: ClaudeStreamBlock::ToolUse { name },
+                        }) => response.tool_calls.push((name, String::new())),
+                        Ok(ClaudeStreamEvent::ContentBlockDelta {
+                            delta: ClaudeStreamDelta::TextDelta { text },
+                        }) => response.text.push_str(&text),
+                        Ok(ClaudeStreamEvent::ContentBlockDelta {
+                            delta: ClaudeStreamDelta::InputJsonDelta { partial_json },
+                        }) => {
+                            if let Some((_, input)) = response.tool_calls.last_mut() {
+                                input.push_str(&partial_json);
+                            }
+
+                            let event = Ok(ToolStreamEvent::InputDelta(partial_json));
+                            return Some((event, (lines, response, false)));
+                        }
+                        Ok(ClaudeStreamEvent::Error { error }) => {
+                            let error = Err(anyhow::anyhow!("Stream error: {error}"));
+                            return Some((error, (lines, response, true)));
+                        }
+                        Ok(_) => {}
+                        Err(e) => return Some((Err(e.into()), (lines, response, true))),
+                    }
+                }
+            });
+
+        Ok(Box::pin(events))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_blocks_are_skipped() {
+        let response: ClaudeToolResponse = serde_json::from_value(serde_json::json!({
+            "content": [
+                { "type": "thinking", "thinking": "Hmm", "signature": "abc" },
+                { "type": "tool_use", "id": "1", "name": "Stop", "input": {} }
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(response.content[0], ClaudeResponseBlock::Unknown);
+        assert!(matches!(
+            &response.content[1],
+            ClaudeResponseBlock::ToolUse { name, .. } if name == "Stop"
+        ));
+    }
 }
@@ -1,9 +1,14 @@
+use std::collections::HashMap;
+
 use async_trait::async_trait;
+use futures_util::StreamExt;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 use crate::{
     brain::{Brain, BrainParameters},
     common::*,
+    stream::response_lines,
     util::remove_italic_actions,
 };
 
@@ -30,40 +35,79 @@ pub struct CohereChatResponse {
     pub text: String,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct CohereBrain;
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CohereParameterDefinition {
+    pub description: String,
+    #[serde(rename = "type")]
+    pub ty: String,
+    pub required: bool,
+}
 
-#[async_trait]
-impl Brain for CohereBrain {
-    fn api_key(&self, debug: bool) -> String {
-        if debug {
-            std::env::var("DEBUG_COHERE_API_KEY").expect("Expected a valid DEBUG Cohere API key")
-        } else {
-            std::env::var("COHERE_API_KEY").expect("Expected a valid Cohere API key")
-        }
-    }
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CohereTool {
+    pub name: String,
+    pub description: String,
+    pub parameter_definitions: HashMap<String, CohereParameterDefinition>,
+}
 
-    fn default_parameters(&self) -> BrainParameters {
-        BrainParameters {
-            debug: true,
-            model: "command-r".to_string(),
-            max_tokens: 300,
-            system_prompt: String::new(),
-            strip_italic_actions: true,
-        }
-    }
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CohereToolRequest {
+    #[serde(flatten)]
+    pub request: CohereChatRequest,
+    pub tools: Vec<CohereTool>,
+    pub force_single_step: bool,
+}
 
-    async fn prompt_chat(
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CohereToolCall {
+    pub name: String,
+    pub parameters: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CohereToolResponse {
+    pub text: String,
+    #[serde(default)]
+    pub tool_calls: Vec<CohereToolCall>,
+}
+
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct CohereStreamRequest {
+    #[serde(flatten)]
+    pub request: CohereChatRequest,
+    pub stream: bool,
+}
+
+/// Streamed responses are newline-delimited JSON events
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event_type", rename_all = "kebab-case")]
+pub enum CohereStreamEvent {
+    TextGeneration {
+        text: String,
+    },
+    StreamEnd {
+        finish_reason: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CohereBrain;
+
+impl CohereBrain {
+    fn create_request(
         &self,
         mut params: BrainParameters,
         mut messages: Vec<ChatMessage>,
-    ) -> anyhow::Result<ChatResponse> {
+    ) -> CohereChatRequest {
         let last_message = messages.pop();
 
         if params.max_tokens < 750 {
             params.max_tokens = 750;
         }
-        let request = CohereChatRequest {
+
+        CohereChatRequest {
             model: params.model,
             max_tokens: params.max_tokens,
             message: last_message.map(|m| m.content.clone()).unwrap_or_default(),
@@ -80,18 +124,21 @@ impl Brain for CohereBrain {
                 })
                 .collect(),
             frequency_penalty: 0.15,
-        };
+        }
+    }
 
+    async fn send_request(
+        &self,
+        debug: bool,
+        request: &impl Serialize,
+    ) -> anyhow::Result<reqwest::Response> {
         let response = self
             .http_client()
             .post("https://api.cohere.ai/v1/chat")
             .header("accept", "application/json")
             .header("content-type", "application/json")
-            .header(
-                "Authorization",
-                format!("bearer {}", self.api_key(params.debug)),
-            )
-            .json(&request)
+            .header("Authorization", format!("bearer {}", self.api_key(debug)))
+            .json(request)
             .send()
             .await?;
 
@@ -102,9 +149,90 @@ impl Brain for CohereBrain {
             return Err(anyhow::anyhow!("Status code: {}\n{:?}", status, text));
         }
 
+        Ok(response)
+    }
+}
+
+/// Cohere describes tool parameters with Python-like types instead of JSON Schema
+fn cohere_parameter_definitions(schema: &Value) -> HashMap<String, CohereParameterDefinition> {
+    let required = schema
+        .get("required")
+        .and_then(|r| r.as_array())
+        .cloned()
+        .unwrap_or_default();
+
+    let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) else {
+        return HashMap::new();
+    };
+
+    properties
+        .iter()
+        .map(|(name, property)| {
+            let types = match property.get("type") {
+                Some(Value::String(ty)) => vec![ty.as_str()],
+                Some(Value::Array(types)) => types.iter().filter_map(|t| t.as_str()).collect(),
+                _ => vec![],
+            };
+
+            let ty = match types.iter().find(|ty| **ty != "null") {
+                Some(&"string") => "str",
+                Some(&"integer") => "int",
+                Some(&"number") => "float",
+                Some(&"boolean") => "bool",
+                Some(&"array") => "list",
+                _ => "dict",
+            };
+
+            let definition = CohereParameterDefinition {
+                description: property
+                    .get("description")
+                    .and_then(|d| d.as_str())
+                    .unwrap_or_default()
+                    .to_string(),
+                ty: ty.to_string(),
+                required: required.contains(&Value::String(name.clone()))
+                    && !types.contains(&"null"),
+            };
+
+            (name.clone(), definition)
+        })
+        .collect()
+}
+
+#[async_trait]
+impl Brain for CohereBrain {
+    fn api_key(&self, debug: bool) -> String {
+        if debug {
+            std::env::var("DEBUG_COHERE_API_KEY").expect("Expected a valid DEBUG Cohere API key")
+        } else {
+            std::env::var("COHERE_API_KEY").expect("Expected a valid Cohere API key")
+        }
+    }
+
+    fn default_parameters(&self) -> BrainParameters {
+        BrainParameters {
+            debug: true,
+            model: "command-r".to_string(),
+            max_tokens: 300,
+            system_prompt: String::new(),
+            strip_italic_actions: true,
+        }
+    }
+
+    async fn prompt_chat(
+        &self,
+        params: BrainParameters,
+        messages: Vec<ChatMessage>,
+    ) -> anyhow::Result<ChatResponse> {
+        let strip_italic_actions = params.strip_italic_actions;
+        let debug = params.debug;
+        let request = self.create_request(params, messages);
+
+        let response = self.send_request(debug, &request).await?;
+
         let mut response: CohereChatResponse = response.json().await?;
 
-        if params.strip_italic_actions {
+        if strip_italic_actions {
             response.text = remove_italic_actions(&response.text);
         }
 
@@ -127,6 +255,85 @@ impl Brain for CohereBrain {
             },
         })
     }
+
+    async fn prompt_chat_stream(
+        &self,
+        params: BrainParameters,
+        messages: Vec<ChatMessage>,
+    ) -> anyhow::Result<ChatStream> {
+        let debug = params.debug;
+        let request = CohereStreamRequest {
+            request: self.create_request(params, messages),
+            stream: true,
+        };
+
+        let response = self.send_request(debug, &request).await?;
+
+        let deltas = response_lines(response).filter_map(|line| async move {
+            let line = match line {
+                Ok(line) if line.trim().is_empty() => return None,
+                Ok(line) => line,
+                Err(e) => return Some(Err(e)),
+            };
+
+            match serde_json::from_str(&line) {
+                Ok(CohereStreamEvent::TextGeneration { text }) => Some(Ok(text)),
+                Ok(CohereStreamEvent::StreamEnd { finish_reason })
+                    if finish_reason.starts_with("ERROR") =>
+                {
+                    Some(Err(anyhow::anyhow!("Stream error: {finish_reason}")))
+                }
+                Ok(_) => None,
+                Err(e) => Some(Err(e.into())),
+            }
+        });
+
+        Ok(Box::pin(deltas))
+    }
+
+    fn supports_tools(&self) -> bool {
+        true
+    }
+
+    async fn prompt_tools(
+        &self,
+        params: BrainParameters,
+        messages: Vec<ChatMessage>,
+        tools: Vec<ToolDefinition>,
+    ) -> anyhow::Result<ToolChatResponse> {
+        let debug = params.debug;
+        let request = CohereToolRequest {
+            request: self.create_request(params, messages),
+            tools: tools
+                .into_iter()
+                .map(|tool| CohereTool {
+                    parameter_definitions: cohere_parameter_definitions(&tool.input_schema),
+                    name: tool.name,
+                    description: tool.description,
+                })
+                .collect(),
+            force_single_step: true,
+        };
+
+        let response = self.send_request(debug, &request).await?;
+        let response: CohereToolResponse = response.json().await?;
+
+        Ok(ToolChatResponse {
+            message: ChatMessage {
+                role: Role::Assistant,
+                content: response.text,
+                image_url: None,
+            },
+            tool_calls: response
+                .tool_calls
+                .into_iter()
+                .map(|call| ToolCall {
+                    name: call.name,
+                    input: call.parameters,
+                })
+                .collect(),
+        })
+    }
 }
 
 fn remove_after_lt(input: &str) -> &str {
@@ -1,3 +1,7 @@
+use std::pin::Pin;
+
+use futures_util::Stream;
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Role {
     User,
@@ -16,8 +20,56 @@ pub struct ChatResponse {
     pub message: ChatMessage,
 }
 
+/// A function the model can call. `input_schema` is a JSON Schema object describing its input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolDefinition {
+    pub name: String,
+    pub description: String,
+    pub input_schema: serde_json::Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolCall {
+    pub name: String,
+    pub input: serde_json::Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolChatResponse {
+    /// Text the model wrote alongside (or instead of) its tool calls
+    pub message: ChatMessage,
+    pub tool_calls: Vec<ToolCall>,
+}
+
+/// Text deltas of a response, in the order they were generated
+pub type ChatStream = Pin<Box<dyn Stream<Item = anyhow::Result<String>> + Send>>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToolStreamEvent {
+    /// A piece of the JSON input of the tool call being generated
+    InputDelta(String),
+    /// The complete response, always the last event
+    Done(ToolChatResponse),
+}
+
+/// Events of a tool response, in the order they were generated
+pub type ToolChatStream = Pin<Box<dyn Stream<Item = anyhow::Result<ToolStreamEvent>> + Send>>;
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum BrainKind {
     CohereCommandR,
     ClaudeHaiku,
+    /// Any server that talks the OpenAI chat-completions protocol, see `OpenAiBrain`
+    OpenAiCompatible,
+}
+
+impl BrainKind {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "cohere" | "command-r" => Some(Self::CohereCommandR),
+            "claude" | "claude-haiku" => Some(Self::ClaudeHaiku),
+            "openai" | "openai-compatible" => Some(Self::OpenAiCompatible),
+            _ => None,
+        }
+    }
 }
@@ -2,4 +2,7 @@ pub mod brain;
 pub mod claude_brain;
 pub mod cohere_brain;
 pub mod common;
+pub mod openai_brain;
+pub mod scripted_brain;
+pub mod stream;
 pub mod util;
@@ -0,0 +1,376 @@
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    brain::{Brain, BrainParameters},
+    common::*,
+    stream::response_lines,
+    util::remove_italic_actions,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct OpenAiImageUrl {
+    pub url: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OpenAiContent {
+    Text { text: String },
+    ImageUrl { image_url: OpenAiImageUrl },
+}
+
+/// Plain text is sent as a string, since not every local server understands content parts
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum OpenAiMessageContent {
+    Text(String),
+    Parts(Vec<OpenAiContent>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct OpenAiChatMessage {
+    pub role: String,
+    pub content: OpenAiMessageContent,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpenAiFunction {
+    pub name: String,
+    pub description: String,
+    pub parameters: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpenAiTool {
+    #[serde(rename = "type")]
+    pub ty: String,
+    pub function: OpenAiFunction,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpenAiRequest {
+    pub model: String,
+    pub max_tokens: usize,
+    pub messages: Vec<OpenAiChatMessage>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub stream: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<OpenAiTool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpenAiFunctionCall {
+    pub name: String,
+    /// JSON-encoded arguments
+    pub arguments: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpenAiToolCall {
+    pub function: OpenAiFunctionCall,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct OpenAiResponseMessage {
+    #[serde(default)]
+    pub content: Option<String>,
+    /// Servers send null or leave it out when there's no call
+    #[serde(default)]
+    pub tool_calls: Option<Vec<OpenAiToolCall>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpenAiChoice {
+    pub message: OpenAiResponseMessage,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpenAiChatResponse {
+    pub choices: Vec<OpenAiChoice>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct OpenAiDelta {
+    #[serde(default)]
+    pub content: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpenAiStreamChoice {
+    #[serde(default)]
+    pub delta: OpenAiDelta,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpenAiStreamChunk {
+    pub choices: Vec<OpenAiStreamChoice>,
+}
+
+/// Talks the OpenAI chat-completions protocol, so it also works with self-hosted servers
+/// like llama.cpp, vLLM and Ollama.
+///
+/// Configured by `OPENAI_BASE_URL` (defaults to the OpenAI API), `OPENAI_MODEL`, `OPENAI_API_KEY`
+/// (optional for local servers) and `OPENAI_TOOLS` (set to `true` if the server supports tool calling).
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OpenAiBrain {
+    pub base_url: String,
+    pub model: String,
+    pub tools: bool,
+}
+
+impl OpenAiBrain {
+    pub fn from_env() -> Self {
+        Self {
+            base_url: std::env::var("OPENAI_BASE_URL")
+                .unwrap_or("https://api.openai.com/v1".to_string()),
+            model: std::env::var("OPENAI_MODEL").unwrap_or("gpt-4o-mini".to_string()),
+            tools: std::env::var("OPENAI_TOOLS").is_ok_and(|tools| tools == "true"),
+        }
+    }
+
+    fn create_request(&self, params: BrainParameters, messages: Vec<ChatMessage>) -> OpenAiRequest {
+        let len = messages.len();
+        let mut openai_messages = Vec::with_capacity(len + 1);
+
+        if !params.system_prompt.is_empty() {
+            openai_messages.push(OpenAiChatMessage {
+                role: "system".to_string(),
+                content: OpenAiMessageContent::Text(params.system_prompt),
+            });
+        }
+
+        for (index, message) in messages.into_iter().enumerate() {
+            let content = match message.image_url {
+                Some(url) if index == len - 1 && message.role == Role::User => {
+                    OpenAiMessageContent::Parts(vec![
+                        OpenAiContent::Text {
+                            text: message.content,
+                        },
+                        OpenAiContent::ImageUrl {
+                            image_url: OpenAiImageUrl { url },
+                        },
+                    ])
+                }
+                _ => OpenAiMessageContent::Text(message.content),
+            };
+
+            openai_messages.push(OpenAiChatMessage {
+                role: match message.role {
+                    Role::User => "user".to_string(),
+                    Role::Assistant => "assistant".to_string(),
+                },
+                content,
+            });
+        }
+
+        OpenAiRequest {
+            model: params.model,
+            max_tokens: params.max_tokens,
+            messages: openai_messages,
+            stream: false,
+            tools: vec![],
+            tool_choice: None,
+        }
+    }
+
+    async fn send_request(
+        &self,
+        debug: bool,
+        request: &impl Serialize,
+    ) -> anyhow::Result<reqwest::Response> {
+        let mut builder = self
+            .http_client()
+            .post(format!(
+                "{}/chat/completions",
+                self.base_url.trim_end_matches('/')
+            ))
+            .header("content-type", "application/json");
+
+        let api_key = self.api_key(debug);
+        if !api_key.is_empty() {
+            builder = builder.header("Authorization", format!("Bearer {api_key}"));
+        }
+
+        let response = builder.json(request).send().await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let mut text = response.text().await?;
+            text.truncate(1800);
+            return Err(anyhow::anyhow!("Status code: {}\n{:?}", status, text));
+        }
+
+        Ok(response)
+    }
+}
+
+#[async_trait]
+impl Brain for OpenAiBrain {
+    fn api_key(&self, _debug: bool) -> String {
+        std::env::var("OPENAI_API_KEY").unwrap_or_default()
+    }
+
+    fn default_parameters(&self) -> BrainParameters {
+        BrainParameters {
+            debug: true,
+            model: self.model.clone(),
+            max_tokens: 400,
+            system_prompt: String::new(),
+            strip_italic_actions: true,
+        }
+    }
+
+    async fn prompt_chat(
+        &self,
+        params: BrainParameters,
+        messages: Vec<ChatMessage>,
+    ) -> anyhow::Result<ChatResponse> {
+        let strip_italic_actions = params.strip_italic_actions;
+        let debug = params.debug;
+        let request = self.create_request(params, messages);
+
+        let response = self.send_request(debug, &request).await?;
+        let response: OpenAiChatResponse = response.json().await?;
+
+        let mut content = response
+            .choices
+            .into_iter()
+            .next()
+            .and_then(|choice| choice.message.content)
+            .unwrap_or_default();
+
+        if strip_italic_actions {
+            content = remove_italic_actions(&content);
+        }
+
+        let text = content.to_uppercase().trim().to_owned();
+        if text.contains("{AWAIT}") {
+            content = "{AWAIT}".to_string();
+        } else if text.contains("{EXIT}") {
+            content = "{EXIT}".to_string();
+        }
+
+        Ok(ChatResponse {
+            message: ChatMessage {
+                role: Role::Assistant,
+                content: content.trim().to_owned(),
+                image_url: None,
+            },
+        })
+    }
+
+    async fn prompt_chat_stream(
+        &self,
+        params: BrainParameters,
+        messages: Vec<ChatMessage>,
+    ) -> anyhow::Result<ChatStream> {
+        let debug = params.debug;
+        let mut request = self.create_request(params, messages);
+        request.stream = true;
+
+        let response = self.send_request(debug, &request).await?;
+
+        let deltas = response_lines(response).filter_map(|line| async move {
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => return Some(Err(e)),
+            };
+            let data = line.strip_prefix("data:")?.trim();
+            if data == "[DONE]" {
+                return None;
+            }
+
+            match serde_json::from_str::<OpenAiStreamChunk>(data) {
+                Ok(chunk) => chunk
+                    .choices
+                    .into_iter()
+                    .next()
+                    .and_then(|choice| choice.delta.content)
+                    .map(Ok),
+                Err(e) => Some(Err(e.into())),
+            }
+        });
+
+        Ok(Box::pin(deltas))
+    }
+
+    fn supports_tools(&self) -> bool {
+        self.tools
+    }
+
+    async fn prompt_tools(
+        &self,
+        params: BrainParameters,
+        messages: Vec<ChatMessage>,
+        tools: Vec<ToolDefinition>,
+    ) -> anyhow::Result<ToolChatResponse> {
+        let debug = params.debug;
+        let mut request = self.create_request(params, messages);
+        request.tools = tools
+            .into_iter()
+            .map(|tool| OpenAiTool {
+                ty: "function".to_string(),
+                function: OpenAiFunction {
+                    name: tool.name,
+                    description: tool.description,
+                    parameters: tool.input_schema,
+                },
+            })
+            .collect();
+        // Forces the model to call one of the tools instead of answering with text
+        request.tool_choice = Some(Value::String("required".to_string()));
+
+        let response = self.send_request(debug, &request).await?;
+        let response: OpenAiChatResponse = response.json().await?;
+        let message = response
+            .choices
+            .into_iter()
+            .next()
+            .map(|choice| choice.message)
+            .unwrap_or_default();
+
+        let tool_calls = message
+            .tool_calls
+            .unwrap_or_default()
+            .into_iter()
+            .map(|call| {
+                Ok(ToolCall {
+                    name: call.function.name,
+                    input: serde_json::from_str(&call.function.arguments)?,
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        Ok(ToolChatResponse {
+            message: ChatMessage {
+                role: Role::Assistant,
+                content: message.content.unwrap_or_default().trim().to_owned(),
+                image_url: None,
+            },
+            tool_calls,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn null_tool_calls_are_read_as_none() {
+        let response: OpenAiChatResponse = serde_json::from_value(serde_json::json!({
+            "choices": [
+                { "message": { "role": "assistant", "content": "Oi", "tool_calls": null } }
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(response.choices[0].message.content.as_deref(), Some("Oi"));
+        assert_eq!(response.choices[0].message.tool_calls, None);
+    }
+}
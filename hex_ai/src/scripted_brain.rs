@@ -0,0 +1,87 @@
+use std::{collections::VecDeque, sync::Mutex};
+
+use async_trait::async_trait;
+
+use crate::{
+    brain::{Brain, BrainParameters},
+    common::*,
+};
+
+/// A brain that replays scripted responses instead of calling a model, for deterministic tests.
+///
+/// Rules are checked first: the first rule whose pattern is found in the last message answers.
+/// Otherwise the next scripted response is used. Every prompt is recorded.
+#[derive(Debug, Default)]
+pub struct ScriptedBrain {
+    script: Mutex<VecDeque<String>>,
+    rules: Vec<(String, String)>,
+    received: Mutex<Vec<Vec<ChatMessage>>>,
+}
+
+impl ScriptedBrain {
+    pub fn new(responses: impl IntoIterator<Item = impl ToString>) -> Self {
+        Self {
+            script: Mutex::new(responses.into_iter().map(|r| r.to_string()).collect()),
+            ..Default::default()
+        }
+    }
+
+    pub fn add_rule(mut self, pattern: impl ToString, response: impl ToString) -> Self {
+        self.rules.push((pattern.to_string(), response.to_string()));
+        self
+    }
+
+    /// Every conversation the brain was prompted with, in order
+    pub fn received(&self) -> Vec<Vec<ChatMessage>> {
+        self.received.lock().unwrap().clone()
+    }
+
+    pub fn remaining_responses(&self) -> usize {
+        self.script.lock().unwrap().len()
+    }
+
+    fn next_response(&self, messages: &[ChatMessage]) -> anyhow::Result<String> {
+        let last_message = messages
+            .last()
+            .map(|m| m.content.as_str())
+            .unwrap_or_default();
+
+        if let Some((_, response)) = self
+            .rules
+            .iter()
+            .find(|(pattern, _)| last_message.contains(pattern.as_str()))
+        {
+            return Ok(response.clone());
+        }
+
+        self.script
+            .lock()
+            .unwrap()
+            .pop_front()
+            .ok_or(anyhow::anyhow!("The script has no more responses"))
+    }
+}
+
+#[async_trait]
+impl Brain for ScriptedBrain {
+    fn api_key(&self, _debug: bool) -> String {
+        String::new()
+    }
+
+    async fn prompt_chat(
+        &self,
+        _params: BrainParameters,
+        messages: Vec<ChatMessage>,
+    ) -> anyhow::Result<ChatResponse> {
+        let response = self.next_response(&messages);
+        self.received.lock().unwrap().push(messages);
+
+        Ok(ChatResponse {
+            message: ChatMessage {
+                role: Role::Assistant,
+                content: response?,
+                image_url: None,
+            },
+        })
+    }
+}
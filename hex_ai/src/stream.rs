@@ -0,0 +1,35 @@
+use std::collections::VecDeque;
+
+use futures_util::{stream, Stream, StreamExt};
+
+/// Splits a streamed HTTP body into lines. Used for both SSE and newline-delimited JSON.
+pub fn response_lines(response: reqwest::Response) -> impl Stream<Item = anyhow::Result<String>> {
+    let state = (response.bytes_stream(), Vec::new(), VecDeque::new());
+
+    stream::unfold(state, |(mut bytes, mut buffer, mut lines)| async move {
+        loop {
+            if let Some(line) = lines.pop_front() {
+                return Some((Ok(line), (bytes, buffer, lines)));
+            }
+
+            match bytes.next().await {
+                Some(Ok(chunk)) => {
+                    buffer.extend_from_slice(&chunk);
+
+                    while let Some(index) = buffer.iter().position(|b| *b == b'\n') {
+                        let line = buffer.drain(..=index).collect::<Vec<_>>();
+                        lines.push_back(String::from_utf8_lossy(&line).trim_end().to_string());
+                    }
+                }
+                Some(Err(e)) => return Some((Err(e.into()), (bytes, buffer, lines))),
+                None if !buffer.is_empty() => {
+                    let line = String::from_utf8_lossy(&buffer).trim_end().to_string();
+                    buffer.clear();
+
+                    return Some((Ok(line), (bytes, buffer, lines)));
+                }
+                None => return None,
+            }
+        }
+    })
+}
@@ -2,6 +2,7 @@ use regex::Regex;
 
 use crate::{
     brain::Brain, claude_brain::ClaudeBrain, cohere_brain::CohereBrain, common::BrainKind,
+    openai_brain::OpenAiBrain,
 };
 
 pub fn remove_italic_actions(input: &str) -> String {
@@ -14,5 +15,6 @@ pub fn get_brain(brain: BrainKind) -> Box<dyn Brain + Send + Sync + 'static> {
     match brain {
         BrainKind::CohereCommandR => Box::new(CohereBrain),
         BrainKind::ClaudeHaiku => Box::new(ClaudeBrain),
+        BrainKind::OpenAiCompatible => Box::new(OpenAiBrain::from_env()),
     }
 }
@@ -0,0 +1,73 @@
+use hex_discord::twilight_model::{
+    channel::{Channel, ChannelType},
+    id::{marker::ChannelMarker, Id},
+};
+
+/// Discord always lists voice channels after the text ones, so they are ordered separately
+fn is_voice(channel: &Channel) -> bool {
+    matches!(
+        channel.kind,
+        ChannelType::GuildVoice | ChannelType::GuildStageVoice
+    )
+}
+
+/// The channels listed together with `channel`, from top to bottom: categories among the
+/// categories, other channels among the channels of the same category
+pub fn siblings(channels: &[Channel], channel: &Channel) -> Vec<Channel> {
+    let mut siblings = channels
+        .iter()
+        .filter(|c| {
+            if channel.kind == ChannelType::GuildCategory {
+                c.kind == ChannelType::GuildCategory
+            } else {
+                c.kind != ChannelType::GuildCategory
+                    && c.parent_id == channel.parent_id
+                    && is_voice(c) == is_voice(channel)
+            }
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+
+    siblings.sort_by_key(|c| (c.position.unwrap_or_default(), c.id));
+    siblings
+}
+
+/// Children of a category, text channels first. `None` lists the categories themselves
+pub fn category_children(
+    channels: &[Channel],
+    category_id: Option<Id<ChannelMarker>>,
+) -> Vec<Channel> {
+    let mut children = channels
+        .iter()
+        .filter(|c| match category_id {
+            Some(category_id) => c.parent_id == Some(category_id),
+            None => c.kind == ChannelType::GuildCategory,
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+
+    children.sort_by_key(|c| (is_voice(c), c.position.unwrap_or_default(), c.id));
+    children
+}
+
+/// Moves the channel to `index` of its siblings, returning the new order
+pub fn move_to(siblings: &[Channel], channel_id: Id<ChannelMarker>, index: usize) -> Vec<Channel> {
+    let mut order = siblings.to_vec();
+    if let Some(current) = order.iter().position(|c| c.id == channel_id) {
+        let channel = order.remove(current);
+        order.insert(index.min(order.len()), channel);
+    }
+
+    order
+}
+
+/// Positions that make the channels appear in the given order. Only the channels whose
+/// position changes are returned.
+pub fn positions_for(order: &[Channel]) -> Vec<(Id<ChannelMarker>, u64)> {
+    order
+        .iter()
+        .enumerate()
+        .filter(|(index, channel)| channel.position != Some(*index as i32))
+        .map(|(index, channel)| (channel.id, index as u64))
+        .collect()
+}
@@ -0,0 +1,259 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use anyhow::Context;
+use hex_database::{
+    bson::oid::ObjectId, ChannelChangeKind, ChannelChangeModel, ChannelSnapshot, HexDatabase,
+    PermissionOverwriteSnapshot,
+};
+use hex_discord::{
+    twilight_model::{
+        channel::{
+            message::component::ButtonStyle,
+            permission_overwrite::{PermissionOverwrite, PermissionOverwriteType},
+            Channel, ChannelType,
+        },
+        guild::Permissions,
+        id::{
+            marker::{ChannelMarker, GuildMarker},
+            Id,
+        },
+    },
+    ActionRowBuilder, ButtonBuilder, DiscordHttpClient,
+};
+use hex_framework::{
+    watcher::{Watcher, WatcherOptions},
+    CommandContext, HexClient, Response,
+};
+
+pub fn snapshot_channel(channel: &Channel) -> ChannelSnapshot {
+    ChannelSnapshot {
+        name: channel.name.clone().unwrap_or_default(),
+        topic: channel.topic.clone(),
+        kind: channel.kind.into(),
+        parent_id: channel.parent_id.map(|id| id.to_string()),
+        position: channel.position,
+        permission_overwrites: channel
+            .permission_overwrites
+            .iter()
+            .flatten()
+            .map(|overwrite| PermissionOverwriteSnapshot {
+                id: overwrite.id.to_string(),
+                kind: overwrite.kind.into(),
+                allow: overwrite.allow.bits().to_string(),
+                deny: overwrite.deny.bits().to_string(),
+            })
+            .collect(),
+    }
+}
+
+fn restore_overwrites(snapshot: &ChannelSnapshot) -> anyhow::Result<Vec<PermissionOverwrite>> {
+    snapshot
+        .permission_overwrites
+        .iter()
+        .map(|overwrite| {
+            Ok(PermissionOverwrite {
+                id: overwrite.id.parse()?,
+                kind: PermissionOverwriteType::from(overwrite.kind),
+                allow: Permissions::from_bits_truncate(overwrite.allow.parse()?),
+                deny: Permissions::from_bits_truncate(overwrite.deny.parse()?),
+            })
+        })
+        .collect()
+}
+
+/// Undoes a single change. `remapped` maps IDs of deleted channels to the IDs of their recreations,
+/// so channels recreated later in the same rollback end up in the recreated category.
+async fn undo_change(
+    http: &DiscordHttpClient,
+    guild_id: Id<GuildMarker>,
+    change: &ChannelChangeModel,
+    remapped: &mut HashMap<String, String>,
+) -> anyhow::Result<()> {
+    let channel_id: Id<ChannelMarker> = change.channel_id.parse()?;
+
+    let Some(before) = &change.before else {
+        http.delete_channel(channel_id).await?;
+        return Ok(());
+    };
+
+    let overwrites = restore_overwrites(before)?;
+    let parent_id = match &before.parent_id {
+        Some(id) => Some(
+            remapped
+                .get(id)
+                .unwrap_or(id)
+                .parse::<Id<ChannelMarker>>()?,
+        ),
+        None => None,
+    };
+
+    match change.kind {
+        ChannelChangeKind::Created => {
+            http.delete_channel(channel_id).await?;
+        }
+        ChannelChangeKind::Edited => {
+            let mut request = http
+                .update_channel(channel_id)
+                .name(&before.name)?
+                .parent_id(parent_id)
+                .permission_overwrites(&overwrites);
+
+            // An empty topic clears the one added by the change
+            let kind = ChannelType::from(before.kind);
+            if let Some(topic) = &before.topic {
+                request = request.topic(topic)?;
+            } else if matches!(
+                kind,
+                ChannelType::GuildText | ChannelType::GuildAnnouncement | ChannelType::GuildForum
+            ) {
+                request = request.topic("")?;
+            }
+
+            if let Some(position) = before.position {
+                request = request.position(position.max(0) as u64);
+            }
+
+            request.await?;
+        }
+        ChannelChangeKind::Deleted => {
+            let mut request = http
+                .create_guild_channel(guild_id, &before.name)?
+                .kind(ChannelType::from(before.kind))
+                .permission_overwrites(&overwrites);
+
+            if let Some(parent_id) = parent_id {
+                request = request.parent_id(parent_id);
+            }
+
+            if let Some(topic) = &before.topic {
+                request = request.topic(topic)?;
+            }
+
+            if let Some(position) = before.position {
+                request = request.position(position.max(0) as u64);
+            }
+
+            let channel = request.await?.model().await?;
+            remapped.insert(change.channel_id.clone(), channel.id.to_string());
+        }
+    }
+
+    Ok(())
+}
+
+/// Undoes every pending channel change of a run, newest first. Returns how many changes were undone
+/// and the errors of the ones that failed.
+pub async fn undo_run(
+    http: &DiscordHttpClient,
+    db: &HexDatabase,
+    guild_id: Id<GuildMarker>,
+    run_id: ObjectId,
+) -> anyhow::Result<(usize, Vec<String>)> {
+    let mut changes = db.channel_changes().find_pending_by_run(run_id).await?;
+    changes.retain(|change| change.guild_id == guild_id.to_string());
+
+    // Deleted categories must be recreated before the channels that were inside them
+    changes.sort_by_key(|change| {
+        change
+            .before
+            .as_ref()
+            .is_none_or(|before| before.kind != u8::from(ChannelType::GuildCategory))
+    });
+
+    let mut remapped = HashMap::new();
+    let mut undone = 0;
+    let mut errors = vec![];
+    for mut change in changes {
+        match undo_change(http, guild_id, &change, &mut remapped).await {
+            Ok(()) => {
+                change.undone = true;
+                db.channel_changes().save(&change).await?;
+                undone += 1;
+            }
+            Err(e) => errors.push(format!("<#{}>: {e}", change.channel_id)),
+        }
+    }
+
+    Ok((undone, errors))
+}
+
+pub fn undo_result_response(undone: usize, errors: &[String]) -> Response {
+    if errors.is_empty() {
+        Response::from(format!("{undone} alteração(ões) de canais desfeita(s).")).success_response()
+    } else {
+        Response::from(format!(
+            "{undone} alteração(ões) desfeita(s), mas {} falharam:\n{}",
+            errors.len(),
+            errors.join("\n")
+        ))
+        .error_response()
+    }
+}
+
+/// Posts a button that lets moderators undo the channel changes of a run
+pub async fn offer_undo(
+    client: Arc<HexClient>,
+    watcher: Arc<Watcher>,
+    db: Arc<HexDatabase>,
+    guild_id: Id<GuildMarker>,
+    channel_id: Id<ChannelMarker>,
+    run_id: ObjectId,
+    changes: usize,
+) -> anyhow::Result<()> {
+    let button = ButtonBuilder::new()
+        .set_custom_id("undo")
+        .set_label("Desfazer")
+        .set_style(ButtonStyle::Danger);
+
+    let response = Response::from(format!(
+        "Hex alterou {changes} canal(is) nesta execução. Moderadores podem desfazer essas alterações."
+    ))
+    .set_components(vec![ActionRowBuilder::new().add_button(button.clone())]);
+
+    let message = client
+        .http
+        .create_message(channel_id)
+        .payload_json(&response.clone().to_json())
+        .await?
+        .model()
+        .await?;
+    let component = watcher
+        .await_single_component(
+            message.id,
+            |interaction| {
+                interaction
+                    .member
+                    .as_ref()
+                    .and_then(|member| member.permissions)
+                    .is_some_and(|permissions| {
+                        permissions
+                            .intersects(Permissions::MANAGE_CHANNELS | Permissions::ADMINISTRATOR)
+                    })
+            },
+            WatcherOptions {
+                timeout: Duration::from_secs(900),
+            },
+        )
+        .await;
+
+    let Ok(Some(component)) = component else {
+        client
+            .http
+            .update_message(message.channel_id, message.id)
+            .payload_json(&response.remove_all_components().to_json())
+            .await?;
+        return Ok(());
+    };
+
+    let mut component_ctx =
+        CommandContext::new(client, Box::new(component), watcher, db.clone(), vec![]);
+    let (undone, errors) = undo_run(&component_ctx.client.http, &db, guild_id, run_id)
+        .await
+        .context("Failed to undo the channel changes")?;
+
+    component_ctx
+        .update_message(undo_result_response(undone, &errors).remove_all_components())
+        .await?;
+
+    Ok(())
+}
@@ -1,20 +1,64 @@
-use std::{collections::HashSet, fmt::Display, time::Duration};
+use std::{
+    collections::HashSet,
+    fmt::Display,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 
-use anyhow::bail;
+use anyhow::{bail, Context};
 use hex_ai::{
-    common::{BrainKind, ChatMessage, Role},
+    brain::Brain,
+    common::{BrainKind, ChatMessage, Role, ToolDefinition, ToolStreamEvent},
     util::get_brain,
 };
+use hex_common::Color;
+use hex_database::{
+    common::DatabaseDateTime, ChannelChangeKind, ChannelChangeModel, ChannelSnapshot,
+    GuildSettingsModel, HexDatabase, MemberWarning, PipelineOutcome, PipelineRunModel,
+    PipelineStep, PipelineStepKind, PipelineStore,
+};
 use hex_discord::{
-    twilight_http::request::AuditLogReason,
-    twilight_model::{channel::ChannelType, id::Id, user::User},
-    UserExtension,
+    twilight_model::{
+        channel::{
+            permission_overwrite::{PermissionOverwrite, PermissionOverwriteType},
+            Channel, ChannelType, Message,
+        },
+        guild::{Permissions, Role as DiscordRole},
+        id::{
+            marker::{ChannelMarker, GuildMarker, MessageMarker, RoleMarker},
+            Id,
+        },
+        user::User,
+        util::Timestamp,
+    },
+    EmbedBuilder, Interaction, UserExtension,
 };
-use hex_framework::CommandContext;
+use hex_framework::{
+    create_channel_confirmation,
+    watcher::{Watcher, WatcherOptions},
+    ChannelEdit, CommandContext, GuildOperations, HexClient, NewChannel, NewRole, Response,
+    RoleEdit,
+};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use tokio_stream::StreamExt;
+
+use crate::{
+    channel_positions::{category_children, move_to, positions_for, siblings},
+    channel_rollback::{offer_undo, snapshot_channel},
+    history::{add_fields_within_limit, run_embed, truncate},
+    mention::message_content,
+    permission_names::{
+        parse_permissions, permission_names, permissions_prompt, ELEVATED_PERMISSIONS,
+    },
+    pipeline_schema::{commands_prompt, validate_command},
+    pipeline_tools::{base_tools, module_tools, tool_call_to_command, TOOLS_PROMPT},
+};
 use serde_json::Value;
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", content = "data")]
 pub enum InputObject {
     Suggestion(UserContentData),
@@ -27,17 +71,40 @@ impl InputObject {
     pub fn is_user_input(&self) -> bool {
         matches!(self, Self::Suggestion(..) | Self::Message(..))
     }
+
+    pub fn image_url(&self) -> Option<String> {
+        match self {
+            Self::Suggestion(data) | Self::Message(data) => data.image_url.clone(),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Suggestion(..) => "Suggestion",
+            Self::Message(..) => "Message",
+            Self::CommandResponse(..) => "CommandResponse",
+            Self::SystemError(..) => "SystemError",
+        }
+    }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
 pub struct UserContentData {
     pub lang: String,
     pub user: UserIdentifier,
     pub content: String,
     pub channel: ChannelRepresentation,
+    /// Image attached to the message, sent to the brain along with the content
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_url: Option<String>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
 pub struct UserIdentifier {
     pub name: String,
     pub uid: u64,
@@ -45,23 +112,98 @@ pub struct UserIdentifier {
     pub notes: Vec<String>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct CommandResponse {
     pub command_type: String,
     pub data: serde_json::Value,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
 pub struct ChannelRepresentation {
     pub id: u64,
     pub name: String,
     pub topic: String,
-    pub kind: String,
+    pub kind: ChannelKind,
     pub category: Option<String>,
+    /// Lower positions are shown first
+    pub position: Option<i32>,
     pub message_count: Option<u32>,
+    /// Voice and stage channels only
+    pub bitrate: Option<u32>,
+    /// Voice and stage channels only. 0 means unlimited
+    pub user_limit: Option<u32>,
+    /// Slowmode, in seconds
+    pub slowmode: Option<u16>,
+    /// Forum channels only
+    pub tags: Option<Vec<String>>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+impl ChannelRepresentation {
+    /// `channels` are the guild channels, used to find the category name
+    pub fn from_channel(channel: &Channel, channels: &[Channel]) -> Self {
+        Self {
+            id: channel.id.get(),
+            name: channel.name.clone().unwrap_or_default(),
+            topic: channel.topic.clone().unwrap_or(String::from("<Empty>")),
+            kind: ChannelKind::from_type(channel.kind),
+            category: channel.parent_id.and_then(|id| {
+                channels
+                    .iter()
+                    .find(|c| c.id == id)
+                    .and_then(|c| c.name.clone())
+            }),
+            position: channel.position,
+            message_count: channel.message_count,
+            bitrate: channel.bitrate,
+            user_limit: channel.user_limit,
+            slowmode: channel.rate_limit_per_user,
+            tags: channel
+                .available_tags
+                .as_ref()
+                .map(|tags| tags.iter().map(|tag| tag.name.clone()).collect()),
+        }
+    }
+}
+
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+pub enum ChannelKind {
+    Chat,
+    Category,
+    Voice,
+    Stage,
+    Forum,
+    Announcement,
+}
+
+impl ChannelKind {
+    pub fn from_type(kind: ChannelType) -> Self {
+        match kind {
+            ChannelType::GuildCategory => Self::Category,
+            ChannelType::GuildVoice => Self::Voice,
+            ChannelType::GuildStageVoice => Self::Stage,
+            ChannelType::GuildForum => Self::Forum,
+            ChannelType::GuildAnnouncement => Self::Announcement,
+            _ => Self::Chat,
+        }
+    }
+
+    pub fn channel_type(&self) -> ChannelType {
+        match self {
+            Self::Chat => ChannelType::GuildText,
+            Self::Category => ChannelType::GuildCategory,
+            Self::Voice => ChannelType::GuildVoice,
+            Self::Stage => ChannelType::GuildStageVoice,
+            Self::Forum => ChannelType::GuildForum,
+            Self::Announcement => ChannelType::GuildAnnouncement,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct CommandObject {
     pub reasoning: String,
     pub cmd: CommandType,
@@ -72,26 +214,44 @@ pub enum Module {
     Channels,
     Moderation,
     Members,
+    Roles,
 }
 
 impl Module {
-    pub const LIST: &'static [Self] = &[Self::Channels, Self::Moderation, Self::Members];
+    pub const LIST: &'static [Self] =
+        &[Self::Channels, Self::Moderation, Self::Members, Self::Roles];
 
     pub fn parse(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
             "channels" => Some(Self::Channels),
             "moderation" => Some(Self::Moderation),
             "members" => Some(Self::Members),
+            "roles" => Some(Self::Roles),
             _ => None,
         }
     }
 
-    pub fn prompt(&self) -> &'static str {
+    /// What the module is for, listed in the system prompt
+    pub fn description(&self) -> &'static str {
         match self {
+            Self::Channels => "for fetching and managing guild channels: chats, categories, voice, stage, forum and announcement channels.",
+            Self::Moderation => "for moderation commands, such as warning, timing out, kicking and banning members.",
+            Self::Members => "for fetching and managing data about guild members like karma and notes.",
+            Self::Roles => "for fetching and managing roles, assigning them to members and setting channel permissions.",
+        }
+    }
+
+    pub fn prompt(&self) -> String {
+        let prompt = match self {
             Self::Channels => include_str!("module_channel.txt"),
             Self::Moderation => include_str!("module_moderation.txt"),
             Self::Members => include_str!("module_members.txt"),
-        }
+            Self::Roles => include_str!("module_roles.txt"),
+        };
+
+        prompt
+            .replace("{commands}", &commands_prompt(Some(*self)))
+            .replace("{permissions}", &permissions_prompt())
     }
 }
 
@@ -101,103 +261,454 @@ impl Display for Module {
             Self::Channels => f.write_str("channels"),
             Self::Moderation => f.write_str("moderation"),
             Self::Members => f.write_str("members"),
+            Self::Roles => f.write_str("roles"),
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", content = "data")]
 pub enum CommandType {
+    /// Imports a module, giving access to more commands
     ImportModule(ImportModuleData),
+    /// Adds karma to a member. Justify the change
     AddKarma(UpdateKarmaData),
+    /// Removes karma from a member. Justify the change
     RemoveKarma(UpdateKarmaData),
+    /// Gets a member when you know EXACTLY which member you are looking for. Returns Member|undefined
     GetMemberData(GetMemberData),
+    /// Gets every member of the guild. Returns [Member]
     GetAllMembersData(
         #[serde(default = "Option::default", skip_serializing_if = "Option::is_none")] Option<()>,
     ),
+    /// Stores a note about a member
     AddNote(AddNoteData),
+    /// Gets every channel of the guild. Returns [Channel]
     GetChannelList(
         #[serde(default = "Option::default", skip_serializing_if = "Option::is_none")] Option<()>,
     ),
+    /// Gets a channel when you know EXACTLY which channel you are looking for. Returns Channel|undefined
     GetChannel(GetChannelData),
+    /// Creates a channel or a category
     CreateChannel(CreateChannelData),
+    /// Deletes a channel. NEVER delete useful channels or channels that are used by the server
     DeleteChannel(DeleteChannelData),
+    /// Edits the name, topic and category of a channel
     EditChannel(EditChannelData),
+    /// Moves a channel to a position among the channels of its category, or a category among the categories
+    SetChannelPosition(SetChannelPositionData),
+    /// Moves a channel right before or after another channel of the same category
+    MoveChannel(MoveChannelData),
+    /// Sorts the channels of a category in the given order
+    ReorderCategory(ReorderCategoryData),
+    /// Sends a message to the user
     SendReply(SendReplyData),
+    /// Asks the user a question and waits for the answer, which comes back as a Message input
+    AskUser(AskUserData),
+    /// Kicks a member from the guild
     KickMember(PunishMemberData),
+    /// Bans a member from the guild
     BanMember(PunishMemberData),
+    /// Stores a warning in the member's record
+    WarnMember(PunishMemberData),
+    /// Stops a member from sending messages, reacting and talking in voice channels for a while
+    TimeoutMember(TimeoutMemberData),
+    /// Removes the timeout of a member
+    RemoveTimeout(PunishMemberData),
+    /// Unbans a user, allowing them to join the guild again
+    UnbanMember(PunishMemberData),
+    /// Gets every role of the guild. Returns [Role]
+    GetRoleList(
+        #[serde(default = "Option::default", skip_serializing_if = "Option::is_none")] Option<()>,
+    ),
+    /// Creates a role
+    CreateRole(CreateRoleData),
+    /// Edits a role. Omitted fields are left unchanged
+    EditRole(EditRoleData),
+    /// Deletes a role. NEVER delete roles that are used by the server
+    DeleteRole(DeleteRoleData),
+    /// Gives a role to a member
+    AssignRole(MemberRoleData),
+    /// Takes a role from a member
+    RemoveRole(MemberRoleData),
+    /// Sets which permissions a role or member has in a channel, replacing the previous overwrite
+    SetChannelPermissionOverwrite(PermissionOverwriteData),
+    /// Stops the command execution
     Stop(#[serde(default = "Option::default", skip_serializing_if = "Option::is_none")] Option<()>),
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+/// Declares the name of every command and the module that gives access to it. `None` means
+/// it's a base command. The matches are exhaustive, so new commands must be listed here
+macro_rules! command_modules {
+    ($($command:ident => $module:expr,)*) => {
+        impl CommandType {
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(Self::$command(..) => stringify!($command),)*
+                }
+            }
+
+            pub fn module(&self) -> Option<Module> {
+                match self {
+                    $(Self::$command(..) => $module,)*
+                }
+            }
+        }
+
+        impl Module {
+            pub fn of(command: &str) -> Option<Self> {
+                match command {
+                    $(stringify!($command) => $module,)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+command_modules! {
+    ImportModule => None,
+    AddKarma => None,
+    RemoveKarma => None,
+    GetMemberData => Some(Module::Members),
+    GetAllMembersData => Some(Module::Members),
+    AddNote => Some(Module::Members),
+    GetChannelList => Some(Module::Channels),
+    GetChannel => Some(Module::Channels),
+    CreateChannel => Some(Module::Channels),
+    DeleteChannel => Some(Module::Channels),
+    EditChannel => Some(Module::Channels),
+    SetChannelPosition => Some(Module::Channels),
+    MoveChannel => Some(Module::Channels),
+    ReorderCategory => Some(Module::Channels),
+    SendReply => None,
+    AskUser => None,
+    KickMember => Some(Module::Moderation),
+    BanMember => Some(Module::Moderation),
+    WarnMember => Some(Module::Moderation),
+    TimeoutMember => Some(Module::Moderation),
+    RemoveTimeout => Some(Module::Moderation),
+    UnbanMember => Some(Module::Moderation),
+    GetRoleList => Some(Module::Roles),
+    CreateRole => Some(Module::Roles),
+    EditRole => Some(Module::Roles),
+    DeleteRole => Some(Module::Roles),
+    AssignRole => Some(Module::Roles),
+    RemoveRole => Some(Module::Roles),
+    SetChannelPermissionOverwrite => Some(Module::Roles),
+    Stop => None,
+}
+
+impl CommandType {
+    /// Commands that change the guild or the database. These are the ones skipped in dry-run mode.
+    pub fn is_mutating(&self) -> bool {
+        matches!(
+            self,
+            Self::CreateChannel(..)
+                | Self::EditChannel(..)
+                | Self::SetChannelPosition(..)
+                | Self::MoveChannel(..)
+                | Self::ReorderCategory(..)
+                | Self::DeleteChannel(..)
+                | Self::KickMember(..)
+                | Self::BanMember(..)
+                | Self::WarnMember(..)
+                | Self::TimeoutMember(..)
+                | Self::RemoveTimeout(..)
+                | Self::UnbanMember(..)
+                | Self::AddKarma(..)
+                | Self::RemoveKarma(..)
+                | Self::AddNote(..)
+                | Self::CreateRole(..)
+                | Self::EditRole(..)
+                | Self::DeleteRole(..)
+                | Self::AssignRole(..)
+                | Self::RemoveRole(..)
+                | Self::SetChannelPermissionOverwrite(..)
+        )
+    }
+
+    /// Destructive commands and commands that can give someone more permissions only run
+    /// after a moderator approves them.
+    pub fn required_permission(&self) -> Option<Permissions> {
+        match self {
+            Self::DeleteChannel(..) => Some(Permissions::MANAGE_CHANNELS),
+            Self::KickMember(..) => Some(Permissions::KICK_MEMBERS),
+            Self::BanMember(..) => Some(Permissions::BAN_MEMBERS),
+            Self::CreateRole(..)
+            | Self::EditRole(..)
+            | Self::DeleteRole(..)
+            | Self::AssignRole(..)
+            | Self::RemoveRole(..) => Some(Permissions::MANAGE_ROLES),
+            // Unknown names are rejected by the executor, so they can be ignored here
+            Self::SetChannelPermissionOverwrite(data) => parse_permissions(&data.allow)
+                .is_ok_and(|allow| allow.intersects(ELEVATED_PERMISSIONS))
+                .then_some(Permissions::MANAGE_ROLES),
+            _ => None,
+        }
+    }
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
 pub struct ImportModuleData {
     pub module_name: String,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
 pub struct UpdateKarmaData {
     pub user_id: u64,
     pub amount: i64,
     pub reason: String,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
 pub struct GetMemberData {
     pub namefilter: Option<String>,
     pub idfilter: Option<u64>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
 pub struct AddNoteData {
     pub member_id: u64,
     pub note: String,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
 pub struct GetChannelData {
     pub namefilter: Option<String>,
     pub idfilter: Option<u64>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
 pub struct CreateChannelData {
     pub channel_name: String,
     pub topic: String,
+    /// Name of the parent category. Null for categories
     pub category: Option<String>,
-    pub is_category: bool,
+    pub kind: ChannelKind,
+    /// Voice and stage channels only, in bits per second (8000 to 96000)
+    #[serde(default)]
+    pub bitrate: Option<u32>,
+    /// Voice and stage channels only, up to 99. Null for unlimited
+    #[serde(default)]
+    pub user_limit: Option<u16>,
+    /// Seconds a member has to wait between messages, up to 21600
+    #[serde(default)]
+    pub slowmode: Option<u16>,
+    /// Names of the tags of a forum channel
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
 pub struct DeleteChannelData {
     pub channel_id: u64,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
 pub struct EditChannelData {
     pub channel_id: u64,
     pub name: String,
     pub topic: String,
+    /// Name of the parent category
     pub category: Option<String>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+pub struct SetChannelPositionData {
+    pub channel_id: u64,
+    /// 0 is the top
+    pub position: u64,
+}
+
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+pub enum Placement {
+    Before,
+    After,
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+pub struct MoveChannelData {
+    pub channel_id: u64,
+    /// The channel to move next to
+    pub target_id: u64,
+    pub placement: Placement,
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+pub struct ReorderCategoryData {
+    /// Null to reorder the categories themselves
+    pub category_id: Option<u64>,
+    /// Channels in the new order, from top to bottom. Channels left out go after them
+    pub channel_ids: Vec<u64>,
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
 pub struct SendReplyData {
     pub content: String,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+pub struct AskUserData {
+    pub question: String,
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
 pub struct PunishMemberData {
     pub user_id: u64,
     pub reason: String,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+pub struct TimeoutMemberData {
+    pub user_id: u64,
+    /// Up to 40320 (28 days)
+    pub duration_minutes: u32,
+    pub reason: String,
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
 pub struct MemberData {
     pub id: u64,
     pub display_name: String,
     pub username: String,
     pub karma: i64,
+    /// Metainformation stored about the member for future use
     pub notes: Vec<String>,
+    /// How many warnings the member received
+    pub warning_count: usize,
+    /// The most recent warnings, with their dates
+    pub warnings: Vec<String>,
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+pub struct RoleRepresentation {
+    pub id: u64,
+    pub name: String,
+    /// Hex color like "#FF0000", "#000000" means no color
+    pub color: String,
+    pub permissions: Vec<String>,
+    /// Shown separately in the member list
+    pub hoist: bool,
+    pub mentionable: bool,
+    /// Higher roles are shown first
+    pub position: i64,
+    /// Managed by an integration (like a bot), can't be edited or assigned
+    pub managed: bool,
+}
+
+impl RoleRepresentation {
+    pub fn from_role(role: &DiscordRole) -> Self {
+        Self {
+            id: role.id.get(),
+            name: role.name.clone(),
+            color: format!("#{:06X}", role.color),
+            permissions: permission_names(role.permissions),
+            hoist: role.hoist,
+            mentionable: role.mentionable,
+            position: role.position,
+            managed: role.managed,
+        }
+    }
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+pub struct CreateRoleData {
+    pub name: String,
+    /// Hex color like "#FF0000". Null for no color
+    pub color: Option<String>,
+    /// Permission names, e.g. ["SEND_MESSAGES", "ATTACH_FILES"]
+    pub permissions: Vec<String>,
+    pub hoist: bool,
+    pub mentionable: bool,
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+pub struct EditRoleData {
+    pub role_id: u64,
+    pub name: Option<String>,
+    pub color: Option<String>,
+    /// Replaces every permission of the role
+    pub permissions: Option<Vec<String>>,
+    pub hoist: Option<bool>,
+    pub mentionable: Option<bool>,
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+pub struct DeleteRoleData {
+    pub role_id: u64,
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+pub struct MemberRoleData {
+    pub user_id: u64,
+    pub role_id: u64,
+}
+
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+pub enum OverwriteTarget {
+    Role,
+    Member,
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+pub struct PermissionOverwriteData {
+    pub channel_id: u64,
+    /// ID of the role or member
+    pub target_id: u64,
+    pub target_kind: OverwriteTarget,
+    /// Permission names explicitly allowed in the channel
+    pub allow: Vec<String>,
+    /// Permission names explicitly denied in the channel
+    pub deny: Vec<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -207,30 +718,628 @@ pub enum PipelineObject {
     MalformmedCommand(String),
 }
 
+/// What started the pipeline. Replies go to the interaction, or to the message
+#[derive(Debug, Clone)]
+pub enum PipelineOrigin {
+    Interaction(CommandContext),
+    Message {
+        client: Arc<HexClient>,
+        watcher: Arc<Watcher>,
+        db: Arc<HexDatabase>,
+        message: Box<Message>,
+        /// The "Executando..." reply, edited to show the progress
+        status: Option<Box<Message>>,
+    },
+    /// Not connected to Discord, e.g. in tests. Replies are kept here and every approval
+    /// gets the same answer
+    Detached {
+        replies: Vec<Response>,
+        approve: bool,
+    },
+}
+
+impl PipelineOrigin {
+    /// The Discord client, watcher and database of the origin. Detached origins have none
+    fn discord(&self) -> Option<(Arc<HexClient>, Arc<Watcher>, Arc<HexDatabase>)> {
+        match self {
+            PipelineOrigin::Interaction(ctx) => {
+                Some((ctx.client.clone(), ctx.watcher.clone(), ctx.db()))
+            }
+            PipelineOrigin::Message {
+                client,
+                watcher,
+                db,
+                ..
+            } => Some((client.clone(), watcher.clone(), db.clone())),
+            PipelineOrigin::Detached { .. } => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct AiCommandPipeline {
-    pub ctx: CommandContext,
+    pub origin: PipelineOrigin,
+    pub store: Arc<dyn PipelineStore>,
+    pub guild_id: Id<GuildMarker>,
+    pub channel_id: Id<ChannelMarker>,
     pub author: User,
     pub history: Vec<PipelineObject>,
-    pub brain: BrainKind,
+    pub brain: Arc<dyn Brain + Send + Sync>,
+    pub guild: Arc<dyn GuildOperations>,
     pub error_counter: u32,
     pub active: bool,
     pub imported_modules: HashSet<Module>,
+    pub dry_run: bool,
+    pub plan: Vec<CommandObject>,
+    pub approval_channel: Option<Id<ChannelMarker>>,
+    pub run: PipelineRunModel,
+    pub channel_changes: usize,
+    /// The input that started the run, reused to describe the answers to AskUser
+    pub user_input: Option<UserContentData>,
+    pub settings: GuildSettingsModel,
+    /// Pause before each prompt, so the brain isn't flooded with requests
+    pub delay: Duration,
 }
 
 impl AiCommandPipeline {
     pub async fn new(ctx: CommandContext) -> anyhow::Result<Self> {
         let author = ctx.author().await?;
+        let guild_id = ctx.guild_id()?;
+        let channel_id = ctx
+            .interaction
+            .channel
+            .as_ref()
+            .map(|c| c.id)
+            .context("Expected a channel")?;
+        let settings = ctx
+            .db()
+            .guild_settings()
+            .get_guild(&guild_id.to_string())
+            .await?;
+
+        Ok(Self::with_origin(
+            ctx.client.clone(),
+            ctx.db(),
+            author,
+            guild_id,
+            channel_id,
+            settings,
+            PipelineOrigin::Interaction(ctx),
+        ))
+    }
+
+    /// Starts a conversation from a message mentioning Hex. Replies to the message
+    pub async fn from_message(
+        client: Arc<HexClient>,
+        watcher: Arc<Watcher>,
+        db: Arc<HexDatabase>,
+        message: Message,
+    ) -> anyhow::Result<Self> {
+        let guild_id = message.guild_id.context("Expected a Guild")?;
+        let settings = db.guild_settings().get_guild(&guild_id.to_string()).await?;
+
+        Ok(Self::with_origin(
+            client.clone(),
+            db.clone(),
+            message.author.clone(),
+            guild_id,
+            message.channel_id,
+            settings,
+            PipelineOrigin::Message {
+                client,
+                watcher,
+                db,
+                message: Box::new(message),
+                status: None,
+            },
+        ))
+    }
+
+    /// Runs without Discord or MongoDB, over the given guild and store. Replies are kept in the
+    /// `Detached` origin and every approval gets `approve`. Used in tests
+    pub fn detached(
+        guild: Arc<dyn GuildOperations>,
+        store: Arc<dyn PipelineStore>,
+        author: User,
+        guild_id: Id<GuildMarker>,
+        channel_id: Id<ChannelMarker>,
+        settings: GuildSettingsModel,
+        approve: bool,
+    ) -> Self {
+        Self::with_origin(
+            guild,
+            store,
+            author,
+            guild_id,
+            channel_id,
+            settings,
+            PipelineOrigin::Detached {
+                replies: vec![],
+                approve,
+            },
+        )
+    }
 
-        Ok(Self {
-            ctx,
+    fn with_origin(
+        guild: Arc<dyn GuildOperations>,
+        store: Arc<dyn PipelineStore>,
+        author: User,
+        guild_id: Id<GuildMarker>,
+        channel_id: Id<ChannelMarker>,
+        settings: GuildSettingsModel,
+        origin: PipelineOrigin,
+    ) -> Self {
+        let run = PipelineRunModel::new(
+            guild_id.to_string(),
+            author.id.to_string(),
+            Some(channel_id.to_string()),
+        );
+
+        Self {
+            guild,
+            origin,
+            store,
+            guild_id,
+            channel_id,
             author,
             history: vec![],
-            brain: BrainKind::ClaudeHaiku,
+            brain: Arc::from(get_brain(
+                settings
+                    .brain
+                    .clone()
+                    .or_else(|| std::env::var("PIPELINE_BRAIN").ok())
+                    .and_then(|brain| BrainKind::parse(&brain))
+                    .unwrap_or(BrainKind::ClaudeHaiku),
+            )),
             error_counter: 0,
             active: true,
             imported_modules: HashSet::new(),
-        })
+            dry_run: false,
+            plan: vec![],
+            // Channel where destructive commands wait for a moderator, from /config or the env
+            approval_channel: settings
+                .approval_channel_id
+                .clone()
+                .or_else(|| std::env::var("MODERATOR_CHANNEL_ID").ok())
+                .and_then(|id| id.parse().ok())
+                .and_then(Id::new_checked),
+            run,
+            channel_changes: 0,
+            user_input: None,
+            settings,
+            delay: Duration::from_millis(1500),
+        }
+    }
+
+    /// Shows what the brain is thinking in the "Executando..." reply
+    async fn show_progress(&self, reasoning: &str) {
+        let result = match &self.origin {
+            PipelineOrigin::Interaction(ctx) if ctx.already_replied => ctx
+                .update_interaction_reply(progress_response(reasoning))
+                .await
+                .map(|_| ()),
+            PipelineOrigin::Message {
+                client,
+                status: Some(status),
+                ..
+            } => client
+                .http
+                .update_message(status.channel_id, status.id)
+                .payload_json(&progress_response(reasoning).to_json())
+                .await
+                .map(|_| ())
+                .map_err(anyhow::Error::from),
+            _ => return,
+        };
+
+        if let Err(e) = result {
+            println!("Cannot update the progress reply: {e}");
+        }
+    }
+
+    /// Replies to the user: to the interaction, or to the message that started the conversation
+    async fn send(&mut self, response: impl Into<Response>) -> anyhow::Result<()> {
+        match &mut self.origin {
+            PipelineOrigin::Interaction(ctx) => {
+                ctx.send(response).await?;
+            }
+            PipelineOrigin::Message {
+                client, message, ..
+            } => {
+                client
+                    .http
+                    .create_message(message.channel_id)
+                    .reply(message.id)
+                    .payload_json(&response.into().to_json())
+                    .await?;
+            }
+            PipelineOrigin::Detached { replies, .. } => replies.push(response.into()),
+        }
+
+        Ok(())
+    }
+
+    /// Posts the "Executando..." reply to a message. Interactions already have one.
+    pub async fn send_status(&mut self) -> anyhow::Result<()> {
+        if let PipelineOrigin::Message {
+            client,
+            message,
+            status,
+            ..
+        } = &mut self.origin
+        {
+            let reply = client
+                .http
+                .create_message(message.channel_id)
+                .reply(message.id)
+                .content("`Executando...`")?
+                .await?
+                .model()
+                .await?;
+
+            *status = Some(Box::new(reply));
+        }
+
+        Ok(())
+    }
+
+    /// Tools the brain can call right now: the base commands plus the ones from imported modules
+    fn tools(&self) -> Vec<ToolDefinition> {
+        let mut tools = base_tools();
+        for module in Module::LIST {
+            if self.imported_modules.contains(module) {
+                tools.extend(module_tools(*module));
+            }
+        }
+
+        tools
+    }
+
+    /// Replaces the brain chosen by `PIPELINE_BRAIN`, e.g. with a `ScriptedBrain` in tests
+    pub fn set_brain(mut self, brain: Arc<dyn Brain + Send + Sync>) -> Self {
+        self.brain = brain;
+        self
+    }
+
+    /// Replaces the Discord guild, e.g. with a `FakeGuild` in tests
+    pub fn set_guild(mut self, guild: Arc<dyn GuildOperations>) -> Self {
+        self.guild = guild;
+        self
+    }
+
+    /// In dry-run mode, mutating commands are recorded into the plan instead of being executed
+    pub fn set_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self.run.dry_run = dry_run;
+        self
+    }
+
+    /// Changes the pause before each prompt, e.g. to zero in tests
+    pub fn set_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Runs the pipeline for a user input until it stops, persisting the whole run in the store
+    pub async fn run(&mut self, input: InputObject) -> anyhow::Result<()> {
+        self.store.create_run(&self.run).await?;
+
+        if let InputObject::Suggestion(data) | InputObject::Message(data) = &input {
+            self.user_input = Some(data.clone());
+        }
+
+        let result = match self.execute_input(input).await {
+            Ok(command) => self.execute(command).await,
+            Err(e) => Err(e),
+        };
+
+        self.run.finish(match &result {
+            Ok(_) => PipelineOutcome::Completed,
+            Err(e) => PipelineOutcome::Failed(e.to_string()),
+        });
+        if let Err(e) = self.store.save_run(&self.run).await {
+            println!("Error while saving the pipeline run: {e}");
+        }
+
+        // Also sent when the run fails, with what was planned until then
+        if self.dry_run {
+            let error = result.as_ref().err().map(|e| e.to_string());
+            if let Err(e) = self.send_plan(error.as_deref()).await {
+                println!("Error while sending the execution plan: {e}");
+            }
+        }
+
+        if let Err(e) = self.log_run().await {
+            println!("Error while logging the pipeline run: {e}");
+        }
+
+        // The button waits for a long time, so it can't hold the pipeline
+        if let Some((client, watcher, db)) =
+            self.origin.discord().filter(|_| self.channel_changes > 0)
+        {
+            let undo = offer_undo(
+                client,
+                watcher,
+                db,
+                self.guild_id,
+                self.channel_id,
+                self.run.id,
+                self.channel_changes,
+            );
+
+            tokio::spawn(async move {
+                if let Err(e) = undo.await {
+                    println!("Error while offering to undo the pipeline run: {e}");
+                }
+            });
+        }
+
+        result
+    }
+
+    /// Posts the finished run to the guild's log channel, if there's one
+    async fn log_run(&self) -> anyhow::Result<()> {
+        let Some(log_channel_id) = self
+            .settings
+            .log_channel_id
+            .as_ref()
+            .and_then(|id| id.parse().ok())
+            .and_then(Id::<ChannelMarker>::new_checked)
+        else {
+            return Ok(());
+        };
+        let Some((client, ..)) = self.origin.discord() else {
+            return Ok(());
+        };
+
+        client
+            .http
+            .create_message(log_channel_id)
+            .payload_json(&Response::from(run_embed(&self.run)).to_json())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Makes the channels appear in the given order, recording every moved channel for undo
+    async fn reorder_channels(&mut self, order: &[Channel]) -> anyhow::Result<()> {
+        let positions = positions_for(order);
+        if positions.is_empty() {
+            return Ok(());
+        }
+
+        let guild_id = self.guild_id;
+        self.guild
+            .set_channel_positions(guild_id, positions.clone())
+            .await?;
+
+        for (channel_id, _) in positions {
+            if let Some(channel) = order.iter().find(|c| c.id == channel_id) {
+                self.record_channel_change(
+                    ChannelChangeKind::Edited,
+                    channel_id,
+                    Some(snapshot_channel(channel)),
+                )
+                .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Stores the state of a channel before a change, so moderators can undo it later
+    async fn record_channel_change(
+        &mut self,
+        kind: ChannelChangeKind,
+        channel_id: Id<ChannelMarker>,
+        before: Option<ChannelSnapshot>,
+    ) -> anyhow::Result<()> {
+        let change = ChannelChangeModel::new(
+            self.run.id,
+            self.run.guild_id.clone(),
+            channel_id.to_string(),
+            kind,
+            before,
+        );
+
+        self.store.create_channel_change(&change).await?;
+        self.channel_changes += 1;
+
+        Ok(())
+    }
+
+    /// Adds an object to the conversation and stores it as a step of the run
+    async fn push_history(&mut self, object: PipelineObject, duration_ms: Option<i64>) {
+        let (kind, object_type, reasoning, content) = match &object {
+            PipelineObject::Input(input) => (
+                PipelineStepKind::Input,
+                Some(input.name().to_string()),
+                None,
+                serde_json::to_string(input).unwrap_or_default(),
+            ),
+            PipelineObject::Command(cmd) => (
+                PipelineStepKind::Command,
+                Some(cmd.cmd.name().to_string()),
+                Some(cmd.reasoning.clone()),
+                serde_json::to_string(&cmd.cmd).unwrap_or_default(),
+            ),
+            PipelineObject::MalformmedCommand(content) => (
+                PipelineStepKind::MalformedCommand,
+                None,
+                None,
+                content.clone(),
+            ),
+        };
+
+        let step = PipelineStep {
+            kind,
+            object_type,
+            reasoning,
+            content,
+            timestamp: DatabaseDateTime::now(),
+            duration_ms,
+        };
+
+        if let Err(e) = self.store.push_run_step(self.run.id, &step).await {
+            println!("Error while storing the pipeline step: {e}");
+        }
+        self.run.push_step(step);
+
+        self.history.push(object);
+        while self.history.len() > 10 {
+            self.history.remove(0);
+        }
+    }
+
+    fn simulated_response(cmd: &CommandType) -> String {
+        match cmd {
+            CommandType::CreateChannel(data) => format!(
+                "Success - Created the channel {}. The parent-category channel name is: {}",
+                data.channel_name,
+                data.category.clone().unwrap_or(String::from("<None>"))
+            ),
+            CommandType::CreateRole(data) => format!("Success - Created the role {}", data.name),
+            _ => "Success".to_string(),
+        }
+    }
+
+    pub async fn request_approval(
+        &mut self,
+        command: &CommandObject,
+        permission: Permissions,
+    ) -> anyhow::Result<bool> {
+        let data = serde_json::to_value(&command.cmd)
+            .ok()
+            .and_then(|v| v.get("data").cloned())
+            .map(|v| serde_json::to_string_pretty(&v).unwrap_or_default())
+            .unwrap_or_default();
+
+        let embed = EmbedBuilder::new_common()
+            .set_color(Color::ORANGE)
+            .set_author_to_user(&self.author)
+            .set_title(format!("Aprovação necessária: {}", command.cmd.name()))
+            .set_description(format!(
+                "Hex quer executar uma ação sensível a partir da sugestão de {}.\n\n**Motivo:** {}\n```json\n{}\n```",
+                self.author.mention(),
+                command.reasoning,
+                data
+            ))
+            .add_footer_text("Apenas moderadores com permissão podem aprovar");
+
+        let author_id = self.author.id;
+        let moderator_role = self
+            .settings
+            .moderator_role_id
+            .as_ref()
+            .and_then(|id| id.parse().ok())
+            .and_then(Id::<RoleMarker>::new_checked);
+        let filter = move |interaction: &Interaction| {
+            let Some(member) = interaction.member.as_ref() else {
+                return false;
+            };
+
+            interaction.author_id() != Some(author_id)
+                && (member.permissions.is_some_and(|permissions| {
+                    permissions.contains(permission)
+                        || permissions.contains(Permissions::ADMINISTRATOR)
+                }) || moderator_role.is_some_and(|role| member.roles.contains(&role)))
+        };
+
+        let approval_channel = self.approval_channel.ok_or_else(|| {
+            anyhow::anyhow!(
+                "No approval channel is set, so the command can't be approved. \
+                A moderator must set one with /config aprovação"
+            )
+        });
+
+        match &mut self.origin {
+            PipelineOrigin::Interaction(ctx) => {
+                ctx.helper()
+                    .create_filtered_confirmation(
+                        Some(approval_channel?),
+                        filter,
+                        Duration::from_secs(600),
+                        false,
+                        embed,
+                    )
+                    .await
+            }
+            PipelineOrigin::Message {
+                client,
+                watcher,
+                db,
+                ..
+            } => {
+                create_channel_confirmation(
+                    client.clone(),
+                    watcher.clone(),
+                    db.clone(),
+                    approval_channel?,
+                    filter,
+                    Duration::from_secs(600),
+                    embed,
+                )
+                .await
+            }
+            PipelineOrigin::Detached { approve, .. } => Ok(*approve),
+        }
+    }
+
+    /// Shows what a dry run would do. `error` is set when the run failed, so the plan is partial
+    pub async fn send_plan(&mut self, error: Option<&str>) -> anyhow::Result<()> {
+        let mut embed = EmbedBuilder::new_common()
+            .set_color(Color::YELLOW)
+            .set_author_to_user(&self.author)
+            .set_title(if error.is_some() {
+                "Plano de execução parcial (simulação)"
+            } else {
+                "Plano de execução (simulação)"
+            });
+
+        let mut description = vec![];
+        if let Some(error) = error {
+            description.push(format!(
+                "A execução falhou antes do fim: {}",
+                truncate(error, 200)
+            ));
+        }
+        if self.plan.is_empty() {
+            description.push("Nenhuma alteração seria feita no servidor.".to_string());
+        }
+        if !description.is_empty() {
+            embed = embed.set_description(description.join("\n"));
+        }
+
+        let steps = self
+            .plan
+            .iter()
+            .enumerate()
+            .map(|(index, step)| {
+                let data = serde_json::to_value(&step.cmd)
+                    .ok()
+                    .and_then(|v| v.get("data").cloned())
+                    .map(|v| serde_json::to_string(&v).unwrap_or_default())
+                    .unwrap_or_default();
+
+                (
+                    format!("{}. {}", index + 1, step.cmd.name()),
+                    format!("{}\n`{}`", step.reasoning, data),
+                )
+            })
+            .collect();
+        let embed = add_fields_within_limit(embed, steps);
+
+        match &mut self.origin {
+            PipelineOrigin::Interaction(ctx) => {
+                ctx.followup_interaction(Response::from(embed).set_ephemeral())
+                    .await?;
+            }
+            PipelineOrigin::Message { .. } | PipelineOrigin::Detached { .. } => {
+                self.send(embed).await?
+            }
+        }
+
+        Ok(())
     }
 
     pub async fn execute_error(&mut self, error: String) -> anyhow::Result<CommandObject> {
@@ -243,15 +1352,49 @@ impl AiCommandPipeline {
         self.execute_input(InputObject::SystemError(error)).await
     }
 
+    /// The pipeline prompt, adjusted to the guild settings
+    fn system_prompt(&self) -> String {
+        let modules = Module::LIST
+            .iter()
+            .filter(|module| self.settings.is_module_enabled(&module.to_string()))
+            .map(|module| format!("\"{module}\" -> {}", module.description()))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut prompt = include_str!("pipeline_prompt.txt")
+            .replace("{commands}", &commands_prompt(None))
+            .replace("{modules}", &modules)
+            .replace("{kick_karma}", &self.settings.kick_karma.to_string())
+            .replace("{ban_karma}", &self.settings.ban_karma.to_string());
+
+        if let Some(language) = &self.settings.language {
+            prompt = format!(
+                "{prompt}\n\nAlways answer in {language}, whatever the user's language is."
+            );
+        }
+
+        if let Some(rules) = &self.settings.custom_rules {
+            prompt = format!("{prompt}\n\nServer rules, written by the guild moderators. Follow them when evaluating suggestions:\n{rules}");
+        }
+
+        prompt
+    }
+
     pub async fn execute_input(&mut self, input: InputObject) -> anyhow::Result<CommandObject> {
-        tokio::time::sleep(Duration::from_millis(1500)).await;
-        self.history.push(PipelineObject::Input(input));
+        tokio::time::sleep(self.delay).await;
+        self.push_history(PipelineObject::Input(input), None).await;
 
-        let brain = get_brain(self.brain);
+        let brain = self.brain.clone();
 
         let mut parameters = brain.default_parameters();
         parameters.max_tokens = 1024;
-        parameters.system_prompt = include_str!("pipeline_prompt.txt").to_string();
+        parameters.system_prompt = self.system_prompt();
+        if let Some(model) = &self.settings.model {
+            parameters.model = model.clone();
+        }
+        if brain.supports_tools() {
+            parameters.system_prompt = format!("{}\n\n{TOOLS_PROMPT}", parameters.system_prompt);
+        }
 
         loop {
             let mut messages = vec![];
@@ -264,11 +1407,11 @@ impl AiCommandPipeline {
 
                         ChatMessage {
                             content: if is_user_input {
-                                format!("<User Input Object. This was sent by a REAL user in a text-channel. The user is unable to interact again. Respond to this with a command object. Maintain the user language.>\n{json}")
+                                format!("<User Input Object. This was sent by a REAL user in a text-channel. The user can only answer you through the AskUser command. Respond to this with a command object. Maintain the user language.>\n{json}")
                             } else {
                                 format!("<Command Pipeline System Object. This was sent by the system. The user cannot see this. Respond to this with a command object.>\n{json}")
                             },
-                            image_url: None,
+                            image_url: input.image_url(),
                             role: Role::User,
                         }
                     }
@@ -291,21 +1434,115 @@ impl AiCommandPipeline {
                 messages.push(message);
             }
 
-            let response = brain.prompt_chat(parameters.clone(), messages).await?;
-            let mut content = response.message.content;
-            content = content
-                .replace("\"data\": {}", "\"data\": null")
-                .replace("\"data\": undefined", "\"data\": null");
+            let started_at = Instant::now();
+            let (content, output) = if brain.supports_tools() {
+                let mut stream = brain
+                    .prompt_tools_stream(parameters.clone(), messages, self.tools())
+                    .await?;
+
+                let mut input = String::new();
+                let mut last_update = Instant::now();
+                let mut response =
+                    Err(anyhow::anyhow!("The brain stream ended without a response"));
+                while let Some(event) = stream.next().await {
+                    match event {
+                        Ok(ToolStreamEvent::InputDelta(delta)) => {
+                            input.push_str(&delta);
+
+                            if last_update.elapsed() >= PROGRESS_INTERVAL {
+                                if let Some(reasoning) = partial_reasoning(&input) {
+                                    self.show_progress(&reasoning).await;
+                                }
+
+                                last_update = Instant::now();
+                            }
+                        }
+                        Ok(ToolStreamEvent::Done(done)) => response = Ok(done),
+                        // A broken chunk or a cut tool call goes back to the brain as a malformed command
+                        Err(e) => {
+                            response = Err(e);
+                            break;
+                        }
+                    }
+                }
+
+                match response.map(|response| {
+                    let call = response.tool_calls.into_iter().next();
+                    (response.message.content, call)
+                }) {
+                    Ok((_, Some(call))) => {
+                        if let Some(reasoning) =
+                            call.input.get("reasoning").and_then(|r| r.as_str())
+                        {
+                            self.show_progress(reasoning).await;
+                        }
+
+                        (
+                            format!("{} {}", call.name, call.input),
+                            Ok(tool_call_to_command(call)),
+                        )
+                    }
+                    Ok((content, None)) => (content, Err(anyhow::anyhow!("No tool was called"))),
+                    Err(e) => (malformed_content(input), Err(e)),
+                }
+            } else {
+                let stream = brain
+                    .prompt_chat_stream(parameters.clone(), messages)
+                    .await?;
+                let content = match &self.origin {
+                    PipelineOrigin::Interaction(ctx) => {
+                        let show_progress = ctx.already_replied;
+                        ctx.stream_interaction_reply(stream, PROGRESS_INTERVAL, |text| {
+                            partial_reasoning(text)
+                                .filter(|_| show_progress)
+                                .map(|reasoning| progress_response(&reasoning))
+                        })
+                        .await
+                    }
+                    PipelineOrigin::Message { .. } | PipelineOrigin::Detached { .. } => {
+                        let content = stream
+                            .collect::<anyhow::Result<Vec<_>>>()
+                            .await
+                            .map(|chunks| chunks.concat());
+                        if let Some(reasoning) = content.as_deref().ok().and_then(partial_reasoning)
+                        {
+                            self.show_progress(&reasoning).await;
+                        }
+
+                        content
+                    }
+                };
+
+                // A broken chunk goes back to the brain as a malformed command
+                match content {
+                    Ok(content) => {
+                        let content = content
+                            .trim()
+                            .replace("\"data\": {}", "\"data\": null")
+                            .replace("\"data\": undefined", "\"data\": null");
+                        let output =
+                            serde_json::from_str::<Value>(&content).map_err(anyhow::Error::from);
+
+                        (content, output)
+                    }
+                    Err(e) => (malformed_content(String::new()), Err(e)),
+                }
+            };
+            let output = output.and_then(|value| {
+                if let Err(errors) = validate_command(&value) {
+                    bail!("The command doesn't match the schema:\n{errors}");
+                }
+
+                Ok(serde_json::from_value::<CommandObject>(value)?)
+            });
+            let duration_ms = started_at.elapsed().as_millis() as i64;
 
             println!("\x1b[33m{}\x1b[0m", content);
-            let output: Result<CommandObject, _> = serde_json::from_str(&content);
 
             match output {
                 Ok(cmd) => {
-                    self.history.push(PipelineObject::Command(cmd.clone()));
-                    while self.history.len() > 10 {
-                        self.history.remove(0);
-                    }
+                    self.push_history(PipelineObject::Command(cmd.clone()), Some(duration_ms))
+                        .await;
 
                     break Ok(cmd);
                 }
@@ -313,36 +1550,34 @@ impl AiCommandPipeline {
                     self.error_counter += 1;
                     if self.error_counter > 3 {
                         self.active = false;
-                        bail!(e);
+                        return Err(e);
                     }
 
                     println!("Malformed command:\n{e}\n{}\n\n", content);
 
-                    self.history
-                        .push(PipelineObject::MalformmedCommand(content.clone()));
+                    self.push_history(
+                        PipelineObject::MalformmedCommand(content.clone()),
+                        Some(duration_ms),
+                    )
+                    .await;
 
-                    self.history.push(PipelineObject::Input(
-                        InputObject::SystemError(format!("<JSON Error>\nCannot parse your JSON command. Rewrite it again. Remember: only ONE command per message, no markdown and text other than JSON, omitting {{}} and undefined fields.\nError: {}", e))  
-                    ));
+                    self.push_history(PipelineObject::Input(
+                        InputObject::SystemError(format!("<JSON Error>\nCannot parse your JSON command. Rewrite it again. Remember: only ONE command per message, no markdown and text other than JSON, omitting {{}} and undefined fields.\nError: {}", e))
+                    ), None).await;
 
-                    while self.history.len() > 10 {
-                        self.history.remove(0);
-                    }
-
-                    tokio::time::sleep(Duration::from_secs(2)).await;
+                    tokio::time::sleep(self.delay).await;
                 }
             }
         }
     }
 
     pub async fn execute(&mut self, mut command: CommandObject) -> anyhow::Result<()> {
-        let http = self.ctx.client.http.clone();
-        let guild_id = self.ctx.interaction.guild_id.unwrap_or(Id::new(1234567));
-        let db = self.ctx.db();
+        let guild = self.guild.clone();
+        let guild_id = self.guild_id;
 
         macro_rules! all_channels {
             () => {{
-                let channels = match http.guild_channels(guild_id).await {
+                let channels = match guild.channels(guild_id).await {
                     Ok(channels) => channels,
                     Err(e) => {
                         command = self
@@ -352,17 +1587,23 @@ impl AiCommandPipeline {
                     }
                 };
 
-                let channels = match channels.models().await {
-                    Ok(channels) => channels,
-                    Err(e) => {
+                channels
+            }};
+        }
+
+        // IDs come from the brain, and `Id::new` panics on 0
+        macro_rules! checked_id {
+            ($id:expr) => {{
+                let id = $id;
+                match Id::new_checked(id) {
+                    Some(id) => id,
+                    None => {
                         command = self
-                            .execute_error(format!("Error while getting all guild channels: {e}"))
+                            .execute_error(format!("Invalid ID: {id}. IDs are never 0"))
                             .await?;
                         continue;
                     }
-                };
-
-                channels
+                }
             }};
         }
 
@@ -371,11 +1612,65 @@ impl AiCommandPipeline {
                 "-> CMD: {:?}\nReasoning: \"{}\"\n",
                 command.cmd, command.reasoning
             );
-            match &command.cmd {
-                CommandType::ImportModule(data) => {
-                    let module = match Module::parse(&data.module_name) {
-                        Some(module) => module,
-                        None => {
+
+            if let Some(module) = command
+                .cmd
+                .module()
+                .filter(|module| !self.imported_modules.contains(module))
+            {
+                command = self
+                    .execute_error(format!(
+                        "{} is a command of the {module} module. Import the module first",
+                        command.cmd.name()
+                    ))
+                    .await?;
+                continue;
+            }
+
+            if self.dry_run && command.cmd.is_mutating() {
+                self.plan.push(command.clone());
+                command = self
+                    .execute_input(InputObject::CommandResponse(CommandResponse {
+                        command_type: command.cmd.name().to_string(),
+                        data: Value::String(Self::simulated_response(&command.cmd)),
+                    }))
+                    .await?;
+                continue;
+            }
+
+            if let Some(permission) = command.cmd.required_permission() {
+                let approved = match self.request_approval(&command, permission).await {
+                    Ok(approved) => approved,
+                    Err(e) => {
+                        command = self
+                            .execute_error(format!(
+                                "Error while asking for moderator approval: {e}"
+                            ))
+                            .await?;
+                        continue;
+                    }
+                };
+
+                if !approved {
+                    command = self
+                        .execute_input(InputObject::CommandResponse(CommandResponse {
+                            command_type: command.cmd.name().to_string(),
+                            data: Value::String(
+                                "Rejected by moderator. The command was NOT executed.".to_string(),
+                            ),
+                        }))
+                        .await?;
+                    continue;
+                }
+            }
+
+            match &command.cmd {
+                CommandType::ImportModule(data) => {
+                    let module = match Module::parse(&data.module_name) {
+                        Some(module) if self.settings.is_module_enabled(&module.to_string()) => {
+                            module
+                        }
+                        _ => {
                             command = self
                                 .execute_error(format!(
                                     "Invalid module name: {}. Valid modules are: {}",
@@ -383,6 +1678,7 @@ impl AiCommandPipeline {
                                     Module::LIST
                                         .iter()
                                         .map(|m| m.to_string())
+                                        .filter(|m| self.settings.is_module_enabled(m))
                                         .collect::<Vec<_>>()
                                         .join(", ")
                                 ))
@@ -411,8 +1707,8 @@ impl AiCommandPipeline {
                         .await?;
                 }
                 CommandType::AddKarma(data) => {
-                    let user_id = Id::new(data.user_id);
-                    let member = match http.guild_member(guild_id, user_id).await {
+                    let user_id = checked_id!(data.user_id);
+                    let member = match guild.member(guild_id, user_id).await {
                         Ok(member) => member,
                         Err(e) => {
                             command = self
@@ -422,22 +1718,12 @@ impl AiCommandPipeline {
                         }
                     };
 
-                    let member = match member.model().await {
-                        Ok(member) => member,
-                        Err(e) => {
-                            command = self
-                                .execute_error(format!("Error while getting member: {e}"))
-                                .await?;
-                            continue;
-                        }
-                    };
-
-                    let mut member = db
-                        .members()
+                    let mut member = self
+                        .store
                         .get_member(&member.user.id.to_string(), &guild_id.to_string())
                         .await?;
                     member.karma += data.amount.abs();
-                    db.members().save(member).await?;
+                    self.store.save_member(member).await?;
 
                     command = self
                         .execute_input(InputObject::CommandResponse(CommandResponse {
@@ -447,8 +1733,8 @@ impl AiCommandPipeline {
                         .await?;
                 }
                 CommandType::GetAllMembersData(..) => {
-                    let members = match http.guild_members(guild_id).limit(1000)?.await {
-                        Ok(members) => members.models().await?,
+                    let members = match guild.members(guild_id).await {
+                        Ok(members) => members,
                         Err(e) => {
                             command = self
                                 .execute_error(format!("Error while getting members: {e}"))
@@ -459,8 +1745,8 @@ impl AiCommandPipeline {
 
                     let mut members_list = vec![];
                     for member in members {
-                        let data = db
-                            .members()
+                        let data = self
+                            .store
                             .get_member(&member.user.id.to_string(), &guild_id.to_string())
                             .await?;
                         members_list.push((member, data));
@@ -474,6 +1760,8 @@ impl AiCommandPipeline {
                             username: m.user.name.to_string(),
                             karma: d.karma,
                             notes: d.notes.clone(),
+                            warning_count: d.warnings.len(),
+                            warnings: warning_lines(&d.warnings),
                         })
                         .collect::<Vec<_>>();
 
@@ -485,18 +1773,8 @@ impl AiCommandPipeline {
                         .await?;
                 }
                 CommandType::RemoveKarma(data) => {
-                    let user_id = Id::new(data.user_id);
-                    let member = match http.guild_member(guild_id, user_id).await {
-                        Ok(member) => member,
-                        Err(e) => {
-                            command = self
-                                .execute_error(format!("Error while getting member: {e}"))
-                                .await?;
-                            continue;
-                        }
-                    };
-
-                    let member = match member.model().await {
+                    let user_id = checked_id!(data.user_id);
+                    let member = match guild.member(guild_id, user_id).await {
                         Ok(member) => member,
                         Err(e) => {
                             command = self
@@ -506,12 +1784,12 @@ impl AiCommandPipeline {
                         }
                     };
 
-                    let mut member = db
-                        .members()
+                    let mut member = self
+                        .store
                         .get_member(&member.user.id.to_string(), &guild_id.to_string())
                         .await?;
                     member.karma -= data.amount.abs();
-                    db.members().save(member).await?;
+                    self.store.save_member(member).await?;
 
                     command = self
                         .execute_input(InputObject::CommandResponse(CommandResponse {
@@ -521,8 +1799,8 @@ impl AiCommandPipeline {
                         .await?;
                 }
                 CommandType::AddNote(data) => {
-                    let user_id = Id::new(data.member_id);
-                    let member = match http.guild_member(guild_id, user_id).await {
+                    let user_id = checked_id!(data.member_id);
+                    let member = match guild.member(guild_id, user_id).await {
                         Ok(member) => member,
                         Err(e) => {
                             command = self
@@ -532,22 +1810,12 @@ impl AiCommandPipeline {
                         }
                     };
 
-                    let member = match member.model().await {
-                        Ok(member) => member,
-                        Err(e) => {
-                            command = self
-                                .execute_error(format!("Error while getting member: {e}"))
-                                .await?;
-                            continue;
-                        }
-                    };
-
-                    let mut member = db
-                        .members()
+                    let mut member = self
+                        .store
                         .get_member(&member.user.id.to_string(), &guild_id.to_string())
                         .await?;
                     member.notes.push(data.note.clone());
-                    db.members().save(member).await?;
+                    self.store.save_member(member).await?;
 
                     command = self
                         .execute_input(InputObject::CommandResponse(CommandResponse {
@@ -567,8 +1835,8 @@ impl AiCommandPipeline {
                         continue;
                     }
 
-                    let members = match http.guild_members(guild_id).limit(1000)?.await {
-                        Ok(members) => members.models().await?,
+                    let members = match guild.members(guild_id).await {
+                        Ok(members) => members,
                         Err(e) => {
                             command = self
                                 .execute_error(format!("Error while getting members: {e}"))
@@ -599,8 +1867,8 @@ impl AiCommandPipeline {
 
                     match member {
                         Some(member) => {
-                            let data = db
-                                .members()
+                            let data = self
+                                .store
                                 .get_member(&member.user.id.to_string(), &guild_id.to_string())
                                 .await?;
 
@@ -610,6 +1878,8 @@ impl AiCommandPipeline {
                                 username: member.user.name.to_string(),
                                 karma: data.karma,
                                 notes: data.notes,
+                                warning_count: data.warnings.len(),
+                                warnings: warning_lines(&data.warnings),
                             };
 
                             command = self
@@ -633,34 +1903,42 @@ impl AiCommandPipeline {
                     }
                 }
                 CommandType::CreateChannel(data) => {
-                    let channel = match http.create_guild_channel(guild_id, &data.channel_name) {
-                        Ok(channel) => channel,
-                        Err(e) => {
-                            command = self
-                                .execute_error(format!("Error while creating the channel: {e}"))
-                                .await?;
-                            continue;
-                        }
-                    };
-
-                    let category_id = if let Some(category) = &data.category {
+                    // Categories can't be inside another category
+                    let category_id = if let Some(category) = data
+                        .category
+                        .as_ref()
+                        .filter(|_| data.kind != ChannelKind::Category)
+                    {
                         let channels = all_channels!();
                         let category = match channels
                             .iter()
                             .find(|c| c.name.as_ref() == Some(category))
                         {
                             Some(category) => category.clone(),
-                            None => match http.create_guild_channel(guild_id, category) {
+                            None => match guild
+                                .create_channel(
+                                    guild_id,
+                                    NewChannel {
+                                        name: category.clone(),
+                                        kind: ChannelType::GuildCategory,
+                                        topic: None,
+                                        parent_id: None,
+                                        bitrate: None,
+                                        user_limit: None,
+                                        rate_limit_per_user: None,
+                                        tags: vec![],
+                                    },
+                                )
+                                .await
+                            {
                                 Ok(category) => {
-                                    match category.kind(ChannelType::GuildCategory).await {
-                                        Ok(category) => category.model().await?,
-                                        Err(e) => {
-                                            command = self
-                                            .execute_error(format!("Error while creating the category for this channel: {e}"))
-                                            .await?;
-                                            continue;
-                                        }
-                                    }
+                                    self.record_channel_change(
+                                        ChannelChangeKind::Created,
+                                        category.id,
+                                        None,
+                                    )
+                                    .await?;
+                                    category
                                 }
                                 Err(e) => {
                                     command = self
@@ -678,44 +1956,73 @@ impl AiCommandPipeline {
                         None
                     };
 
-                    match channel.topic(&data.topic) {
-                        Ok(mut channel) => {
-                            if let Some(category_id) = category_id {
-                                channel = channel.parent_id(category_id);
-                            } else if data.is_category {
-                                channel = channel.kind(ChannelType::GuildCategory);
-                            }
-
-                            let message = match channel.await {
-                                Ok(channel) => {
-                                    let channel = channel.model().await?;
-                                    format!("Success - Created the channel {} with ID {}. The parent-category channel ID is: {}", data.channel_name, channel.id, channel.parent_id.map(|id| id.get().to_string()).unwrap_or(String::from("<None>")))
-                                }
-                                Err(e) => format!("Api Failure. Error: {e}"),
-                            };
+                    // Only text-based channels have a topic
+                    let topic = matches!(
+                        data.kind,
+                        ChannelKind::Chat | ChannelKind::Announcement | ChannelKind::Forum
+                    )
+                    .then(|| data.topic.clone());
+
+                    let message = match guild
+                        .create_channel(
+                            guild_id,
+                            NewChannel {
+                                name: data.channel_name.clone(),
+                                kind: data.kind.channel_type(),
+                                topic,
+                                parent_id: category_id,
+                                bitrate: data.bitrate,
+                                user_limit: data.user_limit,
+                                rate_limit_per_user: data.slowmode,
+                                tags: data.tags.clone().unwrap_or_default(),
+                            },
+                        )
+                        .await
+                    {
+                        Ok(channel) => {
+                            self.record_channel_change(
+                                ChannelChangeKind::Created,
+                                channel.id,
+                                None,
+                            )
+                            .await?;
+                            format!("Success - Created the channel {} with ID {}. The parent-category channel ID is: {}", data.channel_name, channel.id, channel.parent_id.map(|id| id.get().to_string()).unwrap_or(String::from("<None>")))
+                        }
+                        Err(e) => format!("Api Failure. Error: {e}"),
+                    };
 
-                            command = self
-                                .execute_input(InputObject::CommandResponse(CommandResponse {
-                                    command_type: "CreateChannel".to_string(),
-                                    data: Value::String(message),
-                                }))
-                                .await?;
+                    command = self
+                        .execute_input(InputObject::CommandResponse(CommandResponse {
+                            command_type: "CreateChannel".to_string(),
+                            data: Value::String(message),
+                        }))
+                        .await?;
+                }
+                CommandType::DeleteChannel(data) => {
+                    let data_channel_id = data.channel_id;
 
-                            continue;
-                        }
-                        Err(e) => {
+                    let channels = all_channels!();
+                    let channel = match channels.iter().find(|c| c.id.get() == data_channel_id) {
+                        Some(channel) => channel,
+                        None => {
                             command = self
-                                .execute_input(InputObject::SystemError(format!(
-                                    "Error while setting the channel topic: {e}"
-                                )))
+                                .execute_error(format!(
+                                    "Channel not found with ID {}",
+                                    data.channel_id
+                                ))
                                 .await?;
                             continue;
                         }
-                    }
-                }
-                CommandType::DeleteChannel(data) => {
-                    match http.delete_channel(Id::new(data.channel_id)).await {
+                    };
+
+                    match guild.delete_channel(channel.id).await {
                         Ok(..) => {
+                            self.record_channel_change(
+                                ChannelChangeKind::Deleted,
+                                channel.id,
+                                Some(snapshot_channel(channel)),
+                            )
+                            .await?;
                             command = self
                                 .execute_input(InputObject::CommandResponse(CommandResponse {
                                     command_type: "DeleteChannel".to_string(),
@@ -771,39 +2078,193 @@ impl AiCommandPipeline {
                         None
                     };
 
-                    match http.update_channel(channel.id).name(&data.name) {
-                        Ok(channel) => match channel.topic(&data.topic) {
-                            Ok(channel) => {
-                                let message = match channel.parent_id(category_id).await {
-                                    Ok(_) => "Success".to_string(),
-                                    Err(e) => format!("Api Failure. Error: {e}"),
-                                };
+                    let message = match guild
+                        .edit_channel(
+                            channel.id,
+                            ChannelEdit {
+                                name: data.name.clone(),
+                                topic: matches!(
+                                    channel.kind,
+                                    ChannelType::GuildText
+                                        | ChannelType::GuildAnnouncement
+                                        | ChannelType::GuildForum
+                                )
+                                .then(|| data.topic.clone()),
+                                parent_id: category_id,
+                            },
+                        )
+                        .await
+                    {
+                        Ok(_) => {
+                            self.record_channel_change(
+                                ChannelChangeKind::Edited,
+                                channel.id,
+                                Some(snapshot_channel(channel)),
+                            )
+                            .await?;
+                            "Success".to_string()
+                        }
+                        Err(e) => format!("Api Failure. Error: {e}"),
+                    };
 
-                                command = self
-                                    .execute_input(InputObject::CommandResponse(CommandResponse {
-                                        command_type: "EditChannel".to_string(),
-                                        data: Value::String(message),
-                                    }))
-                                    .await?;
+                    command = self
+                        .execute_input(InputObject::CommandResponse(CommandResponse {
+                            command_type: "EditChannel".to_string(),
+                            data: Value::String(message),
+                        }))
+                        .await?;
+                }
+                CommandType::SetChannelPosition(data) => {
+                    let data_channel_id = data.channel_id;
 
-                                continue;
-                            }
-                            Err(e) => {
+                    let channels = all_channels!();
+                    let channel = match channels.iter().find(|c| c.id.get() == data_channel_id) {
+                        Some(channel) => channel,
+                        None => {
+                            command = self
+                                .execute_error(format!(
+                                    "Channel not found with ID {}",
+                                    data.channel_id
+                                ))
+                                .await?;
+                            continue;
+                        }
+                    };
+
+                    let order = move_to(
+                        &siblings(&channels, channel),
+                        channel.id,
+                        data.position as usize,
+                    );
+                    let message = match self.reorder_channels(&order).await {
+                        Ok(_) => "Success".to_string(),
+                        Err(e) => format!("Api Failure. Error: {e}"),
+                    };
+
+                    command = self
+                        .execute_input(InputObject::CommandResponse(CommandResponse {
+                            command_type: "SetChannelPosition".to_string(),
+                            data: Value::String(message),
+                        }))
+                        .await?;
+                }
+                CommandType::MoveChannel(data) => {
+                    let channels = all_channels!();
+                    let (channel, target) = match (
+                        channels.iter().find(|c| c.id.get() == data.channel_id),
+                        channels.iter().find(|c| c.id.get() == data.target_id),
+                    ) {
+                        (Some(channel), Some(target)) => (channel, target),
+                        (None, _) => {
+                            command = self
+                                .execute_error(format!(
+                                    "Channel not found with ID {}",
+                                    data.channel_id
+                                ))
+                                .await?;
+                            continue;
+                        }
+                        (_, None) => {
+                            command = self
+                                .execute_error(format!(
+                                    "Channel not found with ID {}",
+                                    data.target_id
+                                ))
+                                .await?;
+                            continue;
+                        }
+                    };
+
+                    let siblings = siblings(&channels, channel);
+                    if !siblings.iter().any(|c| c.id == target.id) {
+                        command = self
+                            .execute_error("The channels are not in the same list. Both must be categories, or channels of the same kind in the same category. Use EditChannel to change the category first.".to_string())
+                            .await?;
+                        continue;
+                    }
+
+                    let without_channel = siblings
+                        .iter()
+                        .filter(|c| c.id != channel.id)
+                        .collect::<Vec<_>>();
+                    let target_index = without_channel
+                        .iter()
+                        .position(|c| c.id == target.id)
+                        .unwrap_or_default();
+                    let index = match data.placement {
+                        Placement::Before => target_index,
+                        Placement::After => target_index + 1,
+                    };
+
+                    let order = move_to(&siblings, channel.id, index);
+                    let message = match self.reorder_channels(&order).await {
+                        Ok(_) => "Success".to_string(),
+                        Err(e) => format!("Api Failure. Error: {e}"),
+                    };
+
+                    command = self
+                        .execute_input(InputObject::CommandResponse(CommandResponse {
+                            command_type: "MoveChannel".to_string(),
+                            data: Value::String(message),
+                        }))
+                        .await?;
+                }
+                CommandType::ReorderCategory(data) => {
+                    let channels = all_channels!();
+                    let category_id = match data.category_id {
+                        Some(category_id) => match channels.iter().find(|c| {
+                            c.id.get() == category_id && c.kind == ChannelType::GuildCategory
+                        }) {
+                            Some(category) => Some(category.id),
+                            None => {
                                 command = self
                                     .execute_error(format!(
-                                        "Failed to update channel topic. Error: {e}"
+                                        "Category not found with ID {}",
+                                        category_id
                                     ))
                                     .await?;
                                 continue;
                             }
                         },
-                        Err(e) => {
-                            command = self
-                                .execute_error(format!("Failed to update channel name. Error: {e}"))
-                                .await?;
-                            continue;
-                        }
+                        None => None,
+                    };
+
+                    let children = category_children(&channels, category_id);
+                    if let Some(id) = data
+                        .channel_ids
+                        .iter()
+                        .find(|id| !children.iter().any(|c| c.id.get() == **id))
+                    {
+                        command = self
+                            .execute_error(format!("The channel {id} is not in this category"))
+                            .await?;
+                        continue;
                     }
+
+                    let mut order = data
+                        .channel_ids
+                        .iter()
+                        .filter_map(|id| children.iter().find(|c| c.id.get() == *id))
+                        .cloned()
+                        .collect::<Vec<_>>();
+                    order.extend(
+                        children
+                            .iter()
+                            .filter(|c| !data.channel_ids.contains(&c.id.get()))
+                            .cloned(),
+                    );
+
+                    let message = match self.reorder_channels(&order).await {
+                        Ok(_) => "Success".to_string(),
+                        Err(e) => format!("Api Failure. Error: {e}"),
+                    };
+
+                    command = self
+                        .execute_input(InputObject::CommandResponse(CommandResponse {
+                            command_type: "ReorderCategory".to_string(),
+                            data: Value::String(message),
+                        }))
+                        .await?;
                 }
                 CommandType::GetChannel(data) => {
                     let idfilter = data.idfilter;
@@ -842,23 +2303,7 @@ impl AiCommandPipeline {
                         }
                     };
 
-                    let channel = ChannelRepresentation {
-                        id: channel.id.get(),
-                        name: channel.name.clone().unwrap_or_default(),
-                        topic: channel.topic.clone().unwrap_or(String::from("<Empty>")),
-                        category: channel.parent_id.and_then(|id| {
-                            channels
-                                .iter()
-                                .find(|c| c.id.get() == id)
-                                .and_then(|c| c.name.clone())
-                        }),
-                        kind: if channel.kind == ChannelType::GuildCategory {
-                            "Category".to_string()
-                        } else {
-                            "Chat".to_string()
-                        },
-                        message_count: channel.message_count,
-                    };
+                    let channel = ChannelRepresentation::from_channel(channel, &channels);
 
                     command = self
                         .execute_input(InputObject::CommandResponse(CommandResponse {
@@ -874,23 +2319,7 @@ impl AiCommandPipeline {
                     let channels = all_channels!();
                     let channels = channels
                         .iter()
-                        .map(|c| ChannelRepresentation {
-                            id: c.id.get(),
-                            name: c.name.clone().unwrap_or_default(),
-                            topic: c.topic.clone().unwrap_or(String::from("<Empty>")),
-                            category: c.parent_id.and_then(|id| {
-                                channels
-                                    .iter()
-                                    .find(|c| c.id.get() == id)
-                                    .and_then(|c| c.name.clone())
-                            }),
-                            kind: if c.kind == ChannelType::GuildCategory {
-                                "Category".to_string()
-                            } else {
-                                "Chat".to_string()
-                            },
-                            message_count: c.message_count,
-                        })
+                        .map(|c| ChannelRepresentation::from_channel(c, &channels))
                         .collect::<Vec<_>>();
 
                     command = self
@@ -906,7 +2335,7 @@ impl AiCommandPipeline {
                 CommandType::SendReply(reply) => {
                     let content = reply.content.clone();
 
-                    self.ctx.send(content).await?;
+                    self.send(content).await?;
                     command = self
                         .execute_input(InputObject::CommandResponse(CommandResponse {
                             command_type: "SendReply".to_string(),
@@ -914,19 +2343,62 @@ impl AiCommandPipeline {
                         }))
                         .await?;
                 }
-                CommandType::KickMember(data) => {
-                    let user_id = Id::new(data.user_id);
-                    let member = match http.guild_member(guild_id, user_id).await {
-                        Ok(member) => member,
-                        Err(e) => {
+                CommandType::AskUser(data) => {
+                    self.send(data.question.clone()).await?;
+
+                    let author_id = self.author.id;
+                    let answer = match self.origin.discord() {
+                        Some((client, watcher, _)) => watcher
+                            .await_single_message(
+                                self.channel_id,
+                                move |message| {
+                                    let is_answer = message.author.id == author_id;
+                                    if is_answer {
+                                        ASK_USER_ANSWERS.lock().unwrap().insert(message.id);
+                                    }
+
+                                    is_answer
+                                },
+                                WatcherOptions {
+                                    timeout: ASK_USER_TIMEOUT,
+                                },
+                            )
+                            .await
+                            .map(|answer| {
+                                answer.map(|answer| message_content(&answer, client.user_id))
+                            }),
+                        // Nobody can answer a detached pipeline
+                        None => Ok(None),
+                    };
+
+                    let ((content, image_url), user_input) = match (answer, self.user_input.clone())
+                    {
+                        (Ok(Some(answer)), Some(user_input)) => (answer, user_input),
+                        _ => {
                             command = self
-                                .execute_error(format!("Error while getting member: {e}"))
+                                .execute_input(InputObject::CommandResponse(CommandResponse {
+                                    command_type: "AskUser".to_string(),
+                                    data: Value::String(
+                                        "The user didn't answer in time. Continue without the answer or stop."
+                                            .to_string(),
+                                    ),
+                                }))
                                 .await?;
                             continue;
                         }
                     };
 
-                    let member = match member.model().await {
+                    command = self
+                        .execute_input(InputObject::Message(UserContentData {
+                            content,
+                            image_url,
+                            ..user_input
+                        }))
+                        .await?;
+                }
+                CommandType::KickMember(data) => {
+                    let user_id = checked_id!(data.user_id);
+                    let member = match guild.member(guild_id, user_id).await {
                         Ok(member) => member,
                         Err(e) => {
                             command = self
@@ -936,19 +2408,18 @@ impl AiCommandPipeline {
                         }
                     };
 
-                    match http
-                        .remove_guild_member(guild_id, member.user.id)
-                        .reason(&data.reason)
+                    match guild
+                        .kick_member(guild_id, member.user.id, &data.reason)
+                        .await
                     {
-                        Ok(kick) => match kick.await {
-                            Ok(_) => (),
-                            Err(e) => {
-                                command = self
-                                    .execute_error(format!("Error while kicking member: {e}"))
-                                    .await?;
-                                continue;
-                            }
-                        },
+                        Ok(_) => {
+                            command = self
+                                .execute_input(InputObject::CommandResponse(CommandResponse {
+                                    command_type: "KickMember".to_string(),
+                                    data: Value::String("Success".to_string()),
+                                }))
+                                .await?;
+                        }
                         Err(e) => {
                             command = self
                                 .execute_error(format!("Error while kicking member: {e}"))
@@ -958,8 +2429,8 @@ impl AiCommandPipeline {
                     }
                 }
                 CommandType::BanMember(data) => {
-                    let user_id = Id::new(data.user_id);
-                    let member = match http.guild_member(guild_id, user_id).await {
+                    let user_id = checked_id!(data.user_id);
+                    let member = match guild.member(guild_id, user_id).await {
                         Ok(member) => member,
                         Err(e) => {
                             command = self
@@ -969,7 +2440,29 @@ impl AiCommandPipeline {
                         }
                     };
 
-                    let member = match member.model().await {
+                    match guild
+                        .ban_member(guild_id, member.user.id, &data.reason)
+                        .await
+                    {
+                        Ok(_) => {
+                            command = self
+                                .execute_input(InputObject::CommandResponse(CommandResponse {
+                                    command_type: "BanMember".to_string(),
+                                    data: Value::String("Success".to_string()),
+                                }))
+                                .await?;
+                        }
+                        Err(e) => {
+                            command = self
+                                .execute_error(format!("Error while banning member: {e}"))
+                                .await?;
+                            continue;
+                        }
+                    }
+                }
+                CommandType::WarnMember(data) => {
+                    let user_id = checked_id!(data.user_id);
+                    let member = match guild.member(guild_id, user_id).await {
                         Ok(member) => member,
                         Err(e) => {
                             command = self
@@ -979,27 +2472,316 @@ impl AiCommandPipeline {
                         }
                     };
 
-                    match http
-                        .create_ban(guild_id, member.user.id)
-                        .reason(&data.reason)
+                    let mut member = self
+                        .store
+                        .get_member(&member.user.id.to_string(), &guild_id.to_string())
+                        .await?;
+                    member.push_warning(data.reason.clone(), Some(self.author.id.to_string()));
+                    let warnings = member.warnings.len();
+                    self.store.save_member(member).await?;
+
+                    command = self
+                        .execute_input(InputObject::CommandResponse(CommandResponse {
+                            command_type: "WarnMember".to_string(),
+                            data: Value::String(format!(
+                                "Success - The member now has {warnings} warning(s)"
+                            )),
+                        }))
+                        .await?;
+                }
+                CommandType::TimeoutMember(data) => {
+                    let user_id = checked_id!(data.user_id);
+                    let until = Timestamp::from_secs(
+                        DatabaseDateTime::now().timestamp() + data.duration_minutes as i64 * 60,
+                    );
+                    let until = match until {
+                        Ok(until) => until,
+                        Err(e) => {
+                            command = self
+                                .execute_error(format!("Invalid timeout duration: {e}"))
+                                .await?;
+                            continue;
+                        }
+                    };
+
+                    match guild
+                        .timeout_member(guild_id, user_id, Some(until), &data.reason)
+                        .await
                     {
-                        Ok(kick) => match kick.await {
-                            Ok(_) => (),
+                        Ok(_) => {
+                            command = self
+                                .execute_input(InputObject::CommandResponse(CommandResponse {
+                                    command_type: "TimeoutMember".to_string(),
+                                    data: Value::String("Success".to_string()),
+                                }))
+                                .await?;
+                        }
+                        Err(e) => {
+                            command = self
+                                .execute_error(format!("Error while timing out member: {e}"))
+                                .await?;
+                            continue;
+                        }
+                    }
+                }
+                CommandType::RemoveTimeout(data) => {
+                    let user_id = checked_id!(data.user_id);
+                    match guild
+                        .timeout_member(guild_id, user_id, None, &data.reason)
+                        .await
+                    {
+                        Ok(_) => {
+                            command = self
+                                .execute_input(InputObject::CommandResponse(CommandResponse {
+                                    command_type: "RemoveTimeout".to_string(),
+                                    data: Value::String("Success".to_string()),
+                                }))
+                                .await?;
+                        }
+                        Err(e) => {
+                            command = self
+                                .execute_error(format!("Error while removing the timeout: {e}"))
+                                .await?;
+                            continue;
+                        }
+                    }
+                }
+                CommandType::UnbanMember(data) => {
+                    let user_id = checked_id!(data.user_id);
+                    match guild.unban_member(guild_id, user_id, &data.reason).await {
+                        Ok(_) => {
+                            command = self
+                                .execute_input(InputObject::CommandResponse(CommandResponse {
+                                    command_type: "UnbanMember".to_string(),
+                                    data: Value::String("Success".to_string()),
+                                }))
+                                .await?;
+                        }
+                        Err(e) => {
+                            command = self
+                                .execute_error(format!("Error while unbanning member: {e}"))
+                                .await?;
+                            continue;
+                        }
+                    }
+                }
+                CommandType::GetRoleList(..) => {
+                    let message = match guild.roles(guild_id).await {
+                        Ok(roles) => {
+                            let mut roles = roles
+                                .iter()
+                                .map(RoleRepresentation::from_role)
+                                .collect::<Vec<_>>();
+                            roles.sort_by_key(|r| -r.position);
+
+                            serde_json::to_value(roles).unwrap_or(Value::String(
+                                "Failed to serialize the roles. Explain this error to the user."
+                                    .to_string(),
+                            ))
+                        }
+                        Err(e) => {
+                            command = self
+                                .execute_error(format!("Error while getting roles: {e}"))
+                                .await?;
+                            continue;
+                        }
+                    };
+
+                    command = self
+                        .execute_input(InputObject::CommandResponse(CommandResponse {
+                            command_type: "GetRoleList".to_string(),
+                            data: message,
+                        }))
+                        .await?;
+                }
+                CommandType::CreateRole(data) => {
+                    let role =
+                        match parse_role_fields(data.color.as_deref(), Some(&data.permissions)) {
+                            Ok((color, permissions)) => NewRole {
+                                name: data.name.clone(),
+                                color,
+                                permissions: permissions.unwrap_or(Permissions::empty()),
+                                hoist: data.hoist,
+                                mentionable: data.mentionable,
+                            },
                             Err(e) => {
-                                command = self
-                                    .execute_error(format!("Error while kicking member: {e}"))
-                                    .await?;
+                                command = self.execute_error(e.to_string()).await?;
                                 continue;
                             }
-                        },
+                        };
+
+                    let message = match guild.create_role(guild_id, role).await {
+                        Ok(role) => format!(
+                            "Success - Created the role {} with ID {}",
+                            role.name, role.id
+                        ),
+                        Err(e) => format!("Api Failure. Error: {e}"),
+                    };
+
+                    command = self
+                        .execute_input(InputObject::CommandResponse(CommandResponse {
+                            command_type: "CreateRole".to_string(),
+                            data: Value::String(message),
+                        }))
+                        .await?;
+                }
+                CommandType::EditRole(data) => {
+                    let edit =
+                        match parse_role_fields(data.color.as_deref(), data.permissions.as_deref())
+                        {
+                            Ok((color, permissions)) => RoleEdit {
+                                name: data.name.clone(),
+                                color,
+                                permissions,
+                                hoist: data.hoist,
+                                mentionable: data.mentionable,
+                            },
+                            Err(e) => {
+                                command = self.execute_error(e.to_string()).await?;
+                                continue;
+                            }
+                        };
+
+                    let message = match guild
+                        .edit_role(guild_id, checked_id!(data.role_id), edit)
+                        .await
+                    {
+                        Ok(_) => "Success".to_string(),
+                        Err(e) => format!("Api Failure. Error: {e}"),
+                    };
+
+                    command = self
+                        .execute_input(InputObject::CommandResponse(CommandResponse {
+                            command_type: "EditRole".to_string(),
+                            data: Value::String(message),
+                        }))
+                        .await?;
+                }
+                CommandType::DeleteRole(data) => {
+                    match guild.delete_role(guild_id, checked_id!(data.role_id)).await {
+                        Ok(_) => {
+                            command = self
+                                .execute_input(InputObject::CommandResponse(CommandResponse {
+                                    command_type: "DeleteRole".to_string(),
+                                    data: Value::String("Success".to_string()),
+                                }))
+                                .await?;
+                        }
                         Err(e) => {
                             command = self
-                                .execute_error(format!("Error while kicking member: {e}"))
+                                .execute_error(format!("Error while deleting the role: {e}"))
+                                .await?;
+                            continue;
+                        }
+                    }
+                }
+                CommandType::AssignRole(data) => {
+                    match guild
+                        .add_member_role(
+                            guild_id,
+                            checked_id!(data.user_id),
+                            checked_id!(data.role_id),
+                        )
+                        .await
+                    {
+                        Ok(_) => {
+                            command = self
+                                .execute_input(InputObject::CommandResponse(CommandResponse {
+                                    command_type: "AssignRole".to_string(),
+                                    data: Value::String("Success".to_string()),
+                                }))
+                                .await?;
+                        }
+                        Err(e) => {
+                            command = self
+                                .execute_error(format!("Error while assigning the role: {e}"))
+                                .await?;
+                            continue;
+                        }
+                    }
+                }
+                CommandType::RemoveRole(data) => {
+                    match guild
+                        .remove_member_role(
+                            guild_id,
+                            checked_id!(data.user_id),
+                            checked_id!(data.role_id),
+                        )
+                        .await
+                    {
+                        Ok(_) => {
+                            command = self
+                                .execute_input(InputObject::CommandResponse(CommandResponse {
+                                    command_type: "RemoveRole".to_string(),
+                                    data: Value::String("Success".to_string()),
+                                }))
+                                .await?;
+                        }
+                        Err(e) => {
+                            command = self
+                                .execute_error(format!("Error while removing the role: {e}"))
                                 .await?;
                             continue;
                         }
                     }
                 }
+                CommandType::SetChannelPermissionOverwrite(data) => {
+                    let data_channel_id = data.channel_id;
+
+                    let channels = all_channels!();
+                    let channel = match channels.iter().find(|c| c.id.get() == data_channel_id) {
+                        Some(channel) => channel,
+                        None => {
+                            command = self
+                                .execute_error(format!(
+                                    "Channel not found with ID {}",
+                                    data.channel_id
+                                ))
+                                .await?;
+                            continue;
+                        }
+                    };
+
+                    let overwrite = match (
+                        parse_permissions(&data.allow),
+                        parse_permissions(&data.deny),
+                    ) {
+                        (Ok(allow), Ok(deny)) => PermissionOverwrite {
+                            allow,
+                            deny,
+                            id: checked_id!(data.target_id),
+                            kind: match data.target_kind {
+                                OverwriteTarget::Role => PermissionOverwriteType::Role,
+                                OverwriteTarget::Member => PermissionOverwriteType::Member,
+                            },
+                        },
+                        (Err(e), _) | (_, Err(e)) => {
+                            command = self.execute_error(e.to_string()).await?;
+                            continue;
+                        }
+                    };
+
+                    let message = match guild.set_permission_overwrite(channel.id, overwrite).await
+                    {
+                        Ok(_) => {
+                            self.record_channel_change(
+                                ChannelChangeKind::Edited,
+                                channel.id,
+                                Some(snapshot_channel(channel)),
+                            )
+                            .await?;
+                            "Success".to_string()
+                        }
+                        Err(e) => format!("Api Failure. Error: {e}"),
+                    };
+
+                    command = self
+                        .execute_input(InputObject::CommandResponse(CommandResponse {
+                            command_type: "SetChannelPermissionOverwrite".to_string(),
+                            data: Value::String(message),
+                        }))
+                        .await?;
+                }
                 CommandType::Stop(..) => {
                     self.active = false;
                     break;
@@ -1010,3 +2792,86 @@ impl AiCommandPipeline {
         Ok(())
     }
 }
+
+/// Answers to AskUser. The watcher marks them before the mention handler sees them, so replies
+/// that ping Hex don't start another pipeline
+static ASK_USER_ANSWERS: Lazy<Mutex<HashSet<Id<MessageMarker>>>> =
+    Lazy::new(|| Mutex::new(HashSet::new()));
+
+/// Whether the message answers an AskUser of a running pipeline. Forgets the message
+pub fn take_ask_user_answer(message_id: Id<MessageMarker>) -> bool {
+    ASK_USER_ANSWERS.lock().unwrap().remove(&message_id)
+}
+
+/// Minimum time between two edits of the progress reply
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(1500);
+
+/// How long AskUser waits for the answer
+const ASK_USER_TIMEOUT: Duration = Duration::from_secs(180);
+
+/// How many warnings of a member are shown to the brain. Older ones only count
+const RECENT_WARNINGS: usize = 5;
+
+/// The most recent warnings, oldest first
+fn warning_lines(warnings: &[MemberWarning]) -> Vec<String> {
+    warnings[warnings.len().saturating_sub(RECENT_WARNINGS)..]
+        .iter()
+        .map(|warning| {
+            format!(
+                "{}: {}",
+                warning.created_at.format("%Y-%m-%d"),
+                warning.reason
+            )
+        })
+        .collect()
+}
+
+/// Parses the color and permission names given by the brain for a role
+fn parse_role_fields(
+    color: Option<&str>,
+    permissions: Option<&[String]>,
+) -> anyhow::Result<(Option<u32>, Option<Permissions>)> {
+    let color = match color {
+        Some(color) => Some(
+            u32::from_str_radix(color.trim().trim_start_matches('#'), 16).map_err(|_| {
+                anyhow::anyhow!("Invalid color: {color}. Use a hex color like #FF0000")
+            })?,
+        ),
+        None => None,
+    };
+
+    let permissions = permissions.map(parse_permissions).transpose()?;
+
+    Ok((color, permissions))
+}
+
+/// What the brain wrote before its output broke. Never empty, since models reject empty messages
+fn malformed_content(output: String) -> String {
+    if output.trim().is_empty() {
+        "<Interrupted output>".to_string()
+    } else {
+        output
+    }
+}
+
+fn progress_response(reasoning: &str) -> Response {
+    let mut reasoning = reasoning.trim().to_string();
+    if let Some((index, _)) = reasoning.char_indices().nth(1800) {
+        reasoning.truncate(index);
+        reasoning.push_str("...");
+    }
+
+    Response::from(format!("`Executando...`\n>>> {reasoning}"))
+}
+
+/// Extracts the reasoning of a command object that is still being generated
+fn partial_reasoning(json: &str) -> Option<String> {
+    static REASONING: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r#""reasoning"\s*:\s*"((?:[^"\\]|\\.)*)"#).unwrap());
+
+    let escaped = REASONING.captures(json)?.get(1)?.as_str();
+
+    serde_json::from_str::<String>(&format!("\"{escaped}\""))
+        .ok()
+        .filter(|reasoning| !reasoning.trim().is_empty())
+}
@@ -0,0 +1,423 @@
+use std::time::Duration;
+
+use hex_database::GuildSettingsModel;
+use tokio_stream::StreamExt;
+
+use crate::{command_pipeline::Module, prelude::*};
+
+const PANEL_TIMEOUT: Duration = Duration::from_secs(300);
+const NONE_VALUE: &str = "none";
+
+/// Brains the guild can choose, by the name `BrainKind::parse` accepts
+const BRAINS: &[(&str, &str)] = &[
+    ("claude", "Claude"),
+    ("openai", "OpenAI"),
+    ("cohere", "Cohere Command R"),
+];
+
+fn module_label(module: Module) -> &'static str {
+    match module {
+        Module::Channels => "Canais",
+        Module::Moderation => "Moderação",
+        Module::Members => "Membros",
+        Module::Roles => "Cargos",
+    }
+}
+
+fn optional_text(value: &Option<String>) -> String {
+    match value {
+        Some(value) => value.clone(),
+        None => "Padrão".to_string(),
+    }
+}
+
+fn settings_embed(settings: &GuildSettingsModel) -> EmbedBuilder {
+    let modules = Module::LIST
+        .iter()
+        .filter(|module| settings.is_module_enabled(&module.to_string()))
+        .map(|module| module_label(*module))
+        .collect::<Vec<_>>();
+
+    let rules = match &settings.custom_rules {
+        Some(rules) if rules.chars().count() > 1000 => {
+            rules.chars().take(997).collect::<String>() + "..."
+        }
+        Some(rules) => rules.clone(),
+        None => "Nenhuma".to_string(),
+    };
+
+    EmbedBuilder::new_common()
+        .set_color(Color::BLURPLE)
+        .set_title("Configurações do Hex")
+        .add_inlined_field("Cérebro", optional_text(&settings.brain))
+        .add_inlined_field("Modelo", optional_text(&settings.model))
+        .add_inlined_field("Idioma", optional_text(&settings.language))
+        .add_inlined_field(
+            "Módulos",
+            if modules.is_empty() {
+                "Nenhum".to_string()
+            } else {
+                modules.join(", ")
+            },
+        )
+        .add_inlined_field(
+            "Limites de karma",
+            format!(
+                "Expulsão: {}\nBanimento: {}",
+                settings.kick_karma, settings.ban_karma
+            ),
+        )
+        .add_inlined_field(
+            "Cargo de moderação",
+            match &settings.moderator_role_id {
+                Some(id) => format!("<@&{id}>"),
+                None => "Nenhum".to_string(),
+            },
+        )
+        .add_inlined_field(
+            "Canal de logs",
+            match &settings.log_channel_id {
+                Some(id) => format!("<#{id}>"),
+                None => "Nenhum".to_string(),
+            },
+        )
+        .add_inlined_field(
+            "Canal de aprovação",
+            match &settings.approval_channel_id {
+                Some(id) => format!("<#{id}>"),
+                None => "Padrão".to_string(),
+            },
+        )
+        .add_not_inlined_field("Regras do servidor", rules)
+}
+
+/// The panel rows as JSON, since twilight doesn't know the channel and role select menus
+fn settings_components(settings: &GuildSettingsModel) -> Vec<serde_json::Value> {
+    let mut modules = SelectMenuBuilder::new("modules")
+        .set_placeholder("Módulos habilitados")
+        .set_min_max_values(0, Module::LIST.len() as u8);
+    for module in Module::LIST {
+        modules = modules.add_option(
+            module_label(*module),
+            module,
+            Some(module.description().to_string()),
+            settings.is_module_enabled(&module.to_string()),
+        );
+    }
+
+    let mut brains = SelectMenuBuilder::new("brain")
+        .set_placeholder("Cérebro")
+        .add_option("Padrão", NONE_VALUE, None, settings.brain.is_none());
+    for (value, label) in BRAINS {
+        brains = brains.add_option(
+            label,
+            value,
+            None,
+            settings.brain.as_deref() == Some(*value),
+        );
+    }
+
+    // Unselecting the channel or role sends no values, which means "Nenhum"
+    let mut log_channels = AutoSelectMenuBuilder::new(
+        "log_channel",
+        AutoSelectMenuKind::Channel(vec![ChannelType::GuildText, ChannelType::GuildAnnouncement]),
+    )
+    .set_placeholder("Canal de logs")
+    .set_min_max_values(0, 1);
+    if let Some(id) = &settings.log_channel_id {
+        log_channels = log_channels.add_default_value(id);
+    }
+
+    let mut moderator_roles =
+        AutoSelectMenuBuilder::new("moderator_role", AutoSelectMenuKind::Role)
+            .set_placeholder("Cargo de moderação")
+            .set_min_max_values(0, 1);
+    if let Some(id) = &settings.moderator_role_id {
+        moderator_roles = moderator_roles.add_default_value(id);
+    }
+
+    let row = |row: Component| serde_json::to_value(row).unwrap_or_default();
+
+    vec![
+        row(ActionRowBuilder::new().add_select_menu(modules).build()),
+        row(ActionRowBuilder::new().add_select_menu(brains).build()),
+        log_channels.build(),
+        moderator_roles.build(),
+        row(ActionRowBuilder::new()
+            .add_button(
+                ButtonBuilder::new()
+                    .set_custom_id("reset")
+                    .set_label("Restaurar padrões")
+                    .set_style(ButtonStyle::Danger),
+            )
+            .build()),
+    ]
+}
+
+/// Applies a component interaction of the panel to the settings
+fn apply_component(settings: &mut GuildSettingsModel, custom_id: &str, values: &[String]) {
+    let selected = values
+        .first()
+        .filter(|value| value.as_str() != NONE_VALUE)
+        .cloned();
+
+    match custom_id {
+        "modules" => {
+            settings.enabled_modules = if values.len() == Module::LIST.len() {
+                None
+            } else {
+                Some(values.to_vec())
+            }
+        }
+        "brain" => settings.brain = selected,
+        "log_channel" => settings.log_channel_id = selected,
+        "moderator_role" => settings.moderator_role_id = selected,
+        "reset" => {
+            *settings = GuildSettingsModel {
+                id: settings.id,
+                ..GuildSettingsModel::new(settings.guild_id.clone())
+            }
+        }
+        _ => {}
+    }
+}
+
+#[command_group("Veja e altere as configurações do Hex neste servidor")]
+#[name("config")]
+#[guild_only]
+#[permissions(MANAGE_GUILD)]
+pub mod config {
+    use super::*;
+
+    #[subcommand("Veja e altere as configurações do Hex neste servidor")]
+    #[name("ver")]
+    pub async fn view(mut ctx: CommandContext) -> anyhow::Result<()> {
+        let guild_id = ctx.guild_id()?;
+        let db = ctx.db();
+        let mut settings = db.guild_settings().get_guild(&guild_id.to_string()).await?;
+
+        let response = |settings: &GuildSettingsModel| {
+            settings_components(settings)
+                .into_iter()
+                .fold(Response::from(settings_embed(settings)), |response, row| {
+                    response.add_raw_component(row)
+                })
+        };
+
+        // The select menus are JSON, so they're only sent when editing the reply
+        let message = ctx
+            .send(Response::from(settings_embed(&settings)).set_ephemeral())
+            .await?;
+        ctx.update_interaction_reply(response(&settings)).await?;
+
+        let author_id = ctx.author_id();
+        let stream = ctx.watcher.create_component_stream(
+            message.id,
+            move |interaction| interaction.author_id() == Some(author_id),
+            WatcherOptions {
+                timeout: PANEL_TIMEOUT,
+            },
+        );
+        tokio::pin!(stream);
+
+        while let Some(Ok(collected)) = stream.next().await {
+            let Some(InteractionData::MessageComponent(data)) = &collected.data else {
+                break;
+            };
+            let (custom_id, values) = (data.custom_id.clone(), data.values.clone());
+
+            let mut component_ctx =
+                CommandContext::from_with_interaction(&ctx, Box::new(collected));
+            component_ctx.defer_update_message().await?;
+
+            // Other commands may have changed the settings while the panel was open
+            settings = db.guild_settings().get_guild(&guild_id.to_string()).await?;
+            apply_component(&mut settings, &custom_id, &values);
+            db.guild_settings().save(&settings).await?;
+
+            ctx.update_interaction_reply(response(&settings)).await?;
+        }
+
+        ctx.update_interaction_reply(
+            Response::from(settings_embed(&settings)).remove_all_components(),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    #[subcommand("Defina o karma em que a IA deve expulsar e banir membros")]
+    #[name("limites")]
+    pub async fn thresholds(
+        mut ctx: CommandContext,
+        #[rename("expulsão")]
+        #[description("Karma em que membros devem ser expulsos (ex: -100)")]
+        #[max_value(-1)]
+        kick_karma: i64,
+        #[rename("banimento")]
+        #[description(
+            "Karma em que membros devem ser banidos, menor que o de expulsão (ex: -200)"
+        )]
+        #[max_value(-2)]
+        ban_karma: i64,
+    ) -> anyhow::Result<()> {
+        if ban_karma >= kick_karma {
+            ctx.reply(
+                Response::from("o karma de banimento deve ser menor que o de expulsão.")
+                    .error_response()
+                    .set_ephemeral(),
+            )
+            .await?;
+            return Ok(());
+        }
+
+        let guild_id = ctx.guild_id()?;
+        let db = ctx.db();
+        let mut settings = db.guild_settings().get_guild(&guild_id.to_string()).await?;
+        settings.kick_karma = kick_karma;
+        settings.ban_karma = ban_karma;
+        db.guild_settings().save(&settings).await?;
+
+        ctx.reply(
+            Response::from(format!(
+                "membros serão expulsos com {kick_karma} de karma e banidos com {ban_karma}."
+            ))
+            .success_response()
+            .set_ephemeral(),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    #[subcommand("Defina o canal onde moderadores aprovam as ações sensíveis da IA")]
+    #[name("aprovação")]
+    pub async fn approval(
+        mut ctx: CommandContext,
+        #[rename("canal")]
+        #[description("Canal das aprovações. Deixe vazio para usar o padrão do bot")]
+        #[channel_types(GuildText, GuildAnnouncement)]
+        channel: Option<InteractionChannel>,
+    ) -> anyhow::Result<()> {
+        let guild_id = ctx.guild_id()?;
+        let db = ctx.db();
+        let mut settings = db.guild_settings().get_guild(&guild_id.to_string()).await?;
+        settings.approval_channel_id = channel.as_ref().map(|channel| channel.id.to_string());
+        db.guild_settings().save(&settings).await?;
+
+        ctx.reply(
+            Response::from(match channel {
+                Some(channel) => {
+                    format!(
+                        "as ações sensíveis agora são aprovadas em <#{}>.",
+                        channel.id
+                    )
+                }
+                None => {
+                    "as ações sensíveis agora são aprovadas no canal padrão do bot.".to_string()
+                }
+            })
+            .success_response()
+            .set_ephemeral(),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    #[subcommand("Defina as regras do servidor que a IA deve seguir")]
+    #[name("regras")]
+    pub async fn rules(
+        mut ctx: CommandContext,
+        #[rename("texto")]
+        #[description("As regras do servidor. Deixe vazio para removê-las")]
+        rules: Option<String>,
+    ) -> anyhow::Result<()> {
+        let guild_id = ctx.guild_id()?;
+        let db = ctx.db();
+        let mut settings = db.guild_settings().get_guild(&guild_id.to_string()).await?;
+        settings.custom_rules = rules.filter(|rules| !rules.trim().is_empty());
+        db.guild_settings().save(&settings).await?;
+
+        ctx.reply(
+            Response::from(match settings.custom_rules {
+                Some(_) => "as regras do servidor foram atualizadas.",
+                None => "as regras do servidor foram removidas.",
+            })
+            .success_response()
+            .set_ephemeral(),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    #[subcommand_group("Configure as respostas da IA")]
+    #[name("ia")]
+    pub mod ai {
+        use super::*;
+
+        #[subcommand("Defina o idioma em que a IA responde")]
+        #[name("idioma")]
+        pub async fn language(
+            mut ctx: CommandContext,
+            #[rename("idioma")]
+            #[description("Idioma das respostas. Deixe vazio para usar o idioma do usuário")]
+            #[choice("Português", "pt-BR")]
+            #[choice("English", "en-US")]
+            #[choice("Español", "es-ES")]
+            language: Option<String>,
+        ) -> anyhow::Result<()> {
+            let guild_id = ctx.guild_id()?;
+            let db = ctx.db();
+            let mut settings = db.guild_settings().get_guild(&guild_id.to_string()).await?;
+            settings.language = language
+                .map(|language| language.trim().to_string())
+                .filter(|language| !language.is_empty());
+            db.guild_settings().save(&settings).await?;
+
+            ctx.reply(
+                Response::from(match &settings.language {
+                    Some(language) => format!("a IA agora responde em {language}."),
+                    None => "a IA agora responde no idioma do usuário.".to_string(),
+                })
+                .success_response()
+                .set_ephemeral(),
+            )
+            .await?;
+
+            Ok(())
+        }
+
+        #[subcommand("Defina o modelo usado pelo cérebro da IA")]
+        #[name("modelo")]
+        pub async fn model(
+            mut ctx: CommandContext,
+            #[rename("modelo")]
+            #[description(
+                "Nome do modelo (ex: claude-3-haiku-20240307). Deixe vazio para o padrão"
+            )]
+            model: Option<String>,
+        ) -> anyhow::Result<()> {
+            let guild_id = ctx.guild_id()?;
+            let db = ctx.db();
+            let mut settings = db.guild_settings().get_guild(&guild_id.to_string()).await?;
+            settings.model = model
+                .map(|model| model.trim().to_string())
+                .filter(|model| !model.is_empty());
+            db.guild_settings().save(&settings).await?;
+
+            ctx.reply(
+                Response::from(match &settings.model {
+                    Some(model) => format!("a IA agora usa o modelo `{model}`."),
+                    None => "a IA agora usa o modelo padrão do cérebro.".to_string(),
+                })
+                .success_response()
+                .set_ephemeral(),
+            )
+            .await?;
+
+            Ok(())
+        }
+    }
+}
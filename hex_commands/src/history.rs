@@ -0,0 +1,223 @@
+use hex_database::{PipelineOutcome, PipelineRunModel, PipelineStepKind};
+use hex_discord::twilight_model::guild::Permissions;
+
+use crate::{pipeline_schema::command_names, prelude::*};
+
+pub(crate) fn truncate(string: &str, size: usize) -> String {
+    if string.chars().count() > size {
+        string.chars().take(size - 3).collect::<String>() + "..."
+    } else {
+        string.to_string()
+    }
+}
+
+pub(crate) fn run_embed(run: &PipelineRunModel) -> EmbedBuilder {
+    let (color, outcome) = match &run.outcome {
+        PipelineOutcome::Running => (Color::YELLOW, "Em execução ou interrompida".to_string()),
+        PipelineOutcome::Completed => (Color::GREEN, "Concluída".to_string()),
+        PipelineOutcome::Failed(error) => (Color::RED, format!("Falhou: {}", truncate(error, 200))),
+    };
+
+    let input = run
+        .steps
+        .iter()
+        .find(|step| step.kind == PipelineStepKind::Input)
+        .map(|step| truncate(&step.content, 1000))
+        .unwrap_or_default();
+
+    let embed = EmbedBuilder::new_common()
+        .set_color(color)
+        .add_footer_text(format!("ID: {}", run.id))
+        .set_title(if run.dry_run {
+            "Execução da IA (simulação)"
+        } else {
+            "Execução da IA"
+        })
+        .set_description(format!(
+            "**Autor:** <@{}>\n**Início:** <t:{}:f>\n**Resultado:** {}\n```json\n{}\n```",
+            run.user_id,
+            run.started_at.timestamp(),
+            outcome,
+            input
+        ));
+
+    let commands = run
+        .steps
+        .iter()
+        .filter(|step| step.kind == PipelineStepKind::Command)
+        .enumerate()
+        .map(|(index, step)| {
+            (
+                format!(
+                    "{}. {}",
+                    index + 1,
+                    step.object_type.clone().unwrap_or_default()
+                ),
+                format!(
+                    "{}\n`{}`",
+                    step.reasoning.clone().unwrap_or_default(),
+                    step.content
+                ),
+            )
+        })
+        .collect();
+
+    add_fields_within_limit(embed, commands)
+}
+
+/// Shortest value worth showing when a field has to be shortened to fit the embed
+const MIN_FIELD_VALUE: usize = 64;
+/// Room kept for the field that counts the omitted commands
+const OMITTED_FIELD_LENGTH: usize = 64;
+/// Room kept for the page number that `EmbedPagination` appends to the footer
+const PAGE_FOOTER_LENGTH: usize = 32;
+
+/// Adds a field for each command while the embed fits in Discord's limits. Values are shortened
+/// to the room that is left, and the commands that don't fit are counted in a last field
+pub(crate) fn add_fields_within_limit(
+    mut embed: EmbedBuilder,
+    fields: Vec<(String, String)>,
+) -> EmbedBuilder {
+    let total = fields.len();
+
+    for (index, (name, value)) in fields.into_iter().enumerate() {
+        let is_last = index + 1 == total;
+        let reserved = if is_last { 0 } else { OMITTED_FIELD_LENGTH };
+        let name = truncate(&name, 256);
+        let room = EMBED_LENGTH_LIMIT
+            .saturating_sub(embed.length() + reserved + PAGE_FOOTER_LENGTH + name.chars().count())
+            .min(1024);
+
+        let field_limit = if is_last {
+            EMBED_FIELD_LIMIT
+        } else {
+            EMBED_FIELD_LIMIT - 1
+        };
+
+        if room < MIN_FIELD_VALUE || embed.field_count() >= field_limit {
+            return embed.add_not_inlined_field(
+                "...",
+                format!("Mais {} comandos não cabem aqui", total - index),
+            );
+        }
+
+        embed = embed.add_not_inlined_field(name, truncate(&value, room));
+    }
+
+    embed
+}
+
+/// Suggests the pipeline commands whose name contains what was typed
+async fn complete_command_type(
+    _ctx: &CommandContext,
+    value: String,
+) -> anyhow::Result<Vec<CommandOptionChoice>> {
+    let value = value.to_lowercase();
+
+    Ok(command_names()
+        .filter(|name| name.to_lowercase().contains(&value))
+        .take(25)
+        .map(|name| string_choice(name, name))
+        .collect())
+}
+
+#[command("Veja as decisões recentes da IA neste servidor")]
+#[name("historico")]
+#[guild_only]
+#[permissions(MANAGE_GUILD)]
+pub async fn history(
+    mut ctx: CommandContext,
+    #[rename("usuário")]
+    #[description("Mostrar apenas as sugestões deste usuário")]
+    user: Option<User>,
+    #[rename("comando")]
+    #[description("Mostrar apenas execuções que usaram este comando (ex: CreateChannel)")]
+    #[autocomplete(complete_command_type)]
+    command_type: Option<String>,
+    #[rename("dias")]
+    #[description("Mostrar apenas execuções dos últimos N dias")]
+    #[min_value(1)]
+    #[max_value(365)]
+    days: Option<i64>,
+) -> anyhow::Result<()> {
+    let guild_id = ctx.guild_id()?;
+    let user_id = user.map(|user| user.id.to_string());
+    let since = days.map(|days| chrono::Utc::now() - chrono::Duration::days(days.max(0)));
+
+    let runs = ctx
+        .db()
+        .pipeline_runs()
+        .find_recent(
+            &guild_id.to_string(),
+            user_id.as_deref(),
+            command_type.as_deref(),
+            since,
+            25,
+        )
+        .await?;
+
+    if runs.is_empty() {
+        ctx.reply(
+            Response::from("nenhuma execução da IA encontrada com esses filtros.")
+                .error_response()
+                .set_ephemeral(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let pages = runs.iter().map(run_embed).collect::<Vec<_>>();
+    EmbedPagination::new(ctx, pages)
+        .set_ephemeral()
+        .send()
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use hex_database::{common::DatabaseDateTime, PipelineStep};
+
+    use super::*;
+
+    fn step(kind: PipelineStepKind, content: String) -> PipelineStep {
+        PipelineStep {
+            kind,
+            object_type: Some("SendReply".to_string()),
+            reasoning: Some("r".repeat(900)),
+            content,
+            timestamp: DatabaseDateTime::now(),
+            duration_ms: None,
+        }
+    }
+
+    #[test]
+    fn long_runs_fit_in_an_embed() {
+        let mut run = PipelineRunModel::new("1".to_string(), "2".to_string(), None);
+        run.push_step(step(PipelineStepKind::Input, "i".repeat(5000)));
+        for _ in 0..40 {
+            run.push_step(step(PipelineStepKind::Command, "c".repeat(900)));
+        }
+        run.finish(PipelineOutcome::Failed("e".repeat(1000)));
+
+        let embed = run_embed(&run).add_footer_text("Página 25 de 25");
+
+        assert!(embed.length() <= EMBED_LENGTH_LIMIT);
+        assert!(embed.field_count() <= EMBED_FIELD_LIMIT);
+        assert!(embed.field_count() > 1);
+    }
+
+    #[test]
+    fn short_runs_are_not_shortened() {
+        let mut run = PipelineRunModel::new("1".to_string(), "2".to_string(), None);
+        for _ in 0..3 {
+            run.push_step(step(PipelineStepKind::Command, "{}".to_string()));
+        }
+
+        let embed = run_embed(&run);
+
+        assert_eq!(embed.field_count(), 3);
+        assert!(embed.length() > 3 * 900);
+    }
+}
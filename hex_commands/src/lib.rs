@@ -1,4 +1,10 @@
-mod command_pipeline;
+mod channel_positions;
+mod channel_rollback;
+pub mod command_pipeline;
+pub mod mention;
+mod permission_names;
+mod pipeline_schema;
+mod pipeline_tools;
 mod prelude;
 
 use hex_discord::twilight_model::id::Id;
@@ -6,7 +12,7 @@ use hex_framework::Command;
 use once_cell::sync::Lazy;
 use std::collections::HashMap;
 
-type BoxedCommand = Box<(dyn Command + Send + Sync)>;
+type BoxedCommand = Box<dyn Command + Send + Sync>;
 
 macro_rules! register_command {
     ($map:expr, $command_pat:expr) => {{
@@ -18,7 +24,10 @@ macro_rules! register_command {
     }};
 }
 
+mod guild_config;
+mod history;
 mod suggest;
+mod undo;
 mod util;
 
 pub static COMMANDS: Lazy<HashMap<String, BoxedCommand>> = Lazy::new(|| {
@@ -26,6 +35,9 @@ pub static COMMANDS: Lazy<HashMap<String, BoxedCommand>> = Lazy::new(|| {
 
     register_command!(map, util::PingCommand);
     register_command!(map, suggest::SuggestCommand);
+    register_command!(map, history::HistoryCommand);
+    register_command!(map, guild_config::ConfigCommand);
+    register_command!(map, undo::UndoCommand);
 
     map
 });
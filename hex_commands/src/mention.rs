@@ -0,0 +1,128 @@
+use std::sync::Arc;
+
+use hex_database::HexDatabase;
+use hex_discord::{
+    twilight_model::{
+        channel::Message,
+        id::{marker::UserMarker, Id},
+    },
+    UserExtension,
+};
+use hex_framework::{format_wait, watcher::Watcher, Command, Cooldowns, HexClient};
+
+use crate::{command_pipeline::*, suggest::SuggestCommand};
+
+/// Talks with a member who mentioned Hex in a guild channel, running the pipeline with a
+/// `Message` input. Messages from bots, DMs, messages without a mention and answers to a
+/// running pipeline are ignored.
+pub async fn handle_mention(
+    client: Arc<HexClient>,
+    watcher: Arc<Watcher>,
+    db: Arc<HexDatabase>,
+    cooldowns: Arc<Cooldowns>,
+    message: Message,
+) -> anyhow::Result<()> {
+    // Checked first, so every answer is forgotten even if it doesn't mention Hex
+    if take_ask_user_answer(message.id) {
+        return Ok(());
+    }
+
+    if message.author.bot || !message.mentions.iter().any(|m| m.id == client.user_id) {
+        return Ok(());
+    }
+
+    let Some(guild_id) = message.guild_id else {
+        return Ok(());
+    };
+
+    let (content, image_url) = message_content(&message, client.user_id);
+
+    if content.is_empty() && image_url.is_none() {
+        return Ok(());
+    }
+
+    // Conversations run the same pipeline as /sugerir, so they spend the same uses
+    let config = SuggestCommand.command_config();
+    if let Some(wait) = cooldowns.try_use(
+        "sugerir",
+        &config.cooldowns,
+        message.author.id,
+        Some(guild_id),
+    ) {
+        client
+            .http
+            .create_message(message.channel_id)
+            .reply(message.id)
+            .content(&format!(
+                "aguarde {} para falar com o Hex novamente.",
+                format_wait(wait)
+            ))?
+            .await?;
+
+        return Ok(());
+    }
+
+    let channel = client
+        .http
+        .channel(message.channel_id)
+        .await?
+        .model()
+        .await?;
+    let channels = client.http.guild_channels(guild_id).await?.models().await?;
+
+    let author = message.author.clone();
+    let author_member = db
+        .members()
+        .get_member(&author.id.to_string(), &guild_id.to_string())
+        .await?;
+
+    let mut pipeline = AiCommandPipeline::from_message(client, watcher, db, message).await?;
+    pipeline.send_status().await?;
+
+    // Messages don't carry the user's locale, the brain keeps the language of the content
+    let lang = pipeline
+        .settings
+        .language
+        .clone()
+        .unwrap_or_else(|| "pt-BR".to_string());
+
+    pipeline
+        .run(InputObject::Message(UserContentData {
+            lang,
+            user: UserIdentifier {
+                name: author.display_name(),
+                uid: author.id.get(),
+                karma: author_member.karma,
+                notes: author_member.notes,
+            },
+            content,
+            channel: ChannelRepresentation::from_channel(&channel, &channels),
+            image_url,
+        }))
+        .await?;
+
+    Ok(())
+}
+
+/// The text of a message without Hex's mention, and the URL of its first image
+pub fn message_content(message: &Message, hex_id: Id<UserMarker>) -> (String, Option<String>) {
+    let content = message
+        .content
+        .replace(&format!("<@{hex_id}>"), "")
+        .replace(&format!("<@!{hex_id}>"), "")
+        .trim()
+        .to_string();
+
+    let image_url = message
+        .attachments
+        .iter()
+        .find(|attachment| {
+            attachment
+                .content_type
+                .as_ref()
+                .is_some_and(|content_type| content_type.starts_with("image/"))
+        })
+        .map(|attachment| attachment.url.clone());
+
+    (content, image_url)
+}
@@ -0,0 +1,113 @@
+use hex_discord::twilight_model::guild::Permissions;
+
+/// Permission names the brain uses, matching the Discord API names.
+/// twilight's bitflags can't be looked up by name, so the table is kept by hand.
+pub const PERMISSIONS: &[(&str, Permissions)] = &[
+    ("CREATE_INVITE", Permissions::CREATE_INVITE),
+    ("KICK_MEMBERS", Permissions::KICK_MEMBERS),
+    ("BAN_MEMBERS", Permissions::BAN_MEMBERS),
+    ("ADMINISTRATOR", Permissions::ADMINISTRATOR),
+    ("MANAGE_CHANNELS", Permissions::MANAGE_CHANNELS),
+    ("MANAGE_GUILD", Permissions::MANAGE_GUILD),
+    ("ADD_REACTIONS", Permissions::ADD_REACTIONS),
+    ("VIEW_AUDIT_LOG", Permissions::VIEW_AUDIT_LOG),
+    ("PRIORITY_SPEAKER", Permissions::PRIORITY_SPEAKER),
+    ("STREAM", Permissions::STREAM),
+    ("VIEW_CHANNEL", Permissions::VIEW_CHANNEL),
+    ("SEND_MESSAGES", Permissions::SEND_MESSAGES),
+    ("SEND_TTS_MESSAGES", Permissions::SEND_TTS_MESSAGES),
+    ("MANAGE_MESSAGES", Permissions::MANAGE_MESSAGES),
+    ("EMBED_LINKS", Permissions::EMBED_LINKS),
+    ("ATTACH_FILES", Permissions::ATTACH_FILES),
+    ("READ_MESSAGE_HISTORY", Permissions::READ_MESSAGE_HISTORY),
+    ("MENTION_EVERYONE", Permissions::MENTION_EVERYONE),
+    ("USE_EXTERNAL_EMOJIS", Permissions::USE_EXTERNAL_EMOJIS),
+    ("CONNECT", Permissions::CONNECT),
+    ("SPEAK", Permissions::SPEAK),
+    ("MUTE_MEMBERS", Permissions::MUTE_MEMBERS),
+    ("DEAFEN_MEMBERS", Permissions::DEAFEN_MEMBERS),
+    ("MOVE_MEMBERS", Permissions::MOVE_MEMBERS),
+    ("USE_VAD", Permissions::USE_VAD),
+    ("CHANGE_NICKNAME", Permissions::CHANGE_NICKNAME),
+    ("MANAGE_NICKNAMES", Permissions::MANAGE_NICKNAMES),
+    ("MANAGE_ROLES", Permissions::MANAGE_ROLES),
+    ("MANAGE_WEBHOOKS", Permissions::MANAGE_WEBHOOKS),
+    (
+        "MANAGE_GUILD_EXPRESSIONS",
+        Permissions::MANAGE_GUILD_EXPRESSIONS,
+    ),
+    ("USE_SLASH_COMMANDS", Permissions::USE_SLASH_COMMANDS),
+    ("REQUEST_TO_SPEAK", Permissions::REQUEST_TO_SPEAK),
+    ("MANAGE_EVENTS", Permissions::MANAGE_EVENTS),
+    ("MANAGE_THREADS", Permissions::MANAGE_THREADS),
+    ("CREATE_PUBLIC_THREADS", Permissions::CREATE_PUBLIC_THREADS),
+    (
+        "CREATE_PRIVATE_THREADS",
+        Permissions::CREATE_PRIVATE_THREADS,
+    ),
+    ("USE_EXTERNAL_STICKERS", Permissions::USE_EXTERNAL_STICKERS),
+    (
+        "SEND_MESSAGES_IN_THREADS",
+        Permissions::SEND_MESSAGES_IN_THREADS,
+    ),
+    (
+        "USE_EMBEDDED_ACTIVITIES",
+        Permissions::USE_EMBEDDED_ACTIVITIES,
+    ),
+    ("MODERATE_MEMBERS", Permissions::MODERATE_MEMBERS),
+    ("USE_SOUNDBOARD", Permissions::USE_SOUNDBOARD),
+    ("SEND_VOICE_MESSAGES", Permissions::SEND_VOICE_MESSAGES),
+];
+
+/// Permissions that let a member moderate or reconfigure the guild. Granting them needs a
+/// moderator's approval
+pub const ELEVATED_PERMISSIONS: Permissions = Permissions::ADMINISTRATOR
+    .union(Permissions::MANAGE_GUILD)
+    .union(Permissions::MANAGE_ROLES)
+    .union(Permissions::MANAGE_CHANNELS)
+    .union(Permissions::MANAGE_WEBHOOKS)
+    .union(Permissions::MANAGE_MESSAGES)
+    .union(Permissions::MANAGE_THREADS)
+    .union(Permissions::MANAGE_NICKNAMES)
+    .union(Permissions::MANAGE_GUILD_EXPRESSIONS)
+    .union(Permissions::MANAGE_EVENTS)
+    .union(Permissions::KICK_MEMBERS)
+    .union(Permissions::BAN_MEMBERS)
+    .union(Permissions::MODERATE_MEMBERS)
+    .union(Permissions::MUTE_MEMBERS)
+    .union(Permissions::DEAFEN_MEMBERS)
+    .union(Permissions::MOVE_MEMBERS)
+    .union(Permissions::MENTION_EVERYONE)
+    .union(Permissions::VIEW_AUDIT_LOG);
+
+/// Parses a list of permission names, failing on the first unknown one
+pub fn parse_permissions(names: &[String]) -> anyhow::Result<Permissions> {
+    let mut permissions = Permissions::empty();
+
+    for name in names {
+        let name = name.trim().to_uppercase().replace(' ', "_");
+        match PERMISSIONS.iter().find(|(n, _)| *n == name) {
+            Some((_, permission)) => permissions |= *permission,
+            None => anyhow::bail!("Unknown permission: {name}"),
+        }
+    }
+
+    Ok(permissions)
+}
+
+pub fn permission_names(permissions: Permissions) -> Vec<String> {
+    PERMISSIONS
+        .iter()
+        .filter(|(_, permission)| permissions.contains(*permission))
+        .map(|(name, _)| name.to_string())
+        .collect()
+}
+
+/// The permission names, separated by commas, to list them in prompts
+pub fn permissions_prompt() -> String {
+    PERMISSIONS
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
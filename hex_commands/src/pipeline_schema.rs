@@ -0,0 +1,311 @@
+use std::collections::HashMap;
+
+use jsonschema::JSONSchema;
+use once_cell::sync::Lazy;
+use schemars::{gen::SchemaSettings, JsonSchema};
+use serde_json::Value;
+
+use crate::command_pipeline::{
+    ChannelRepresentation, CommandObject, CommandType, MemberData, Module, RoleRepresentation,
+};
+
+/// Schema of a single `CommandType` variant, extracted from the generated enum schema
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandSchema {
+    pub name: String,
+    pub description: String,
+    /// Schema of the `data` field. `None` for commands without data
+    pub data: Option<Value>,
+}
+
+static COMMAND_SCHEMAS: Lazy<Vec<CommandSchema>> = Lazy::new(|| {
+    let schema = schema_of::<CommandType>();
+    let variants = schema
+        .get("oneOf")
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default();
+
+    variants
+        .iter()
+        .filter_map(|variant| {
+            let properties = variant.get("properties")?;
+            let name = properties.get("type")?.get("enum")?.get(0)?.as_str()?;
+            let data = properties
+                .get("data")
+                .filter(|data| data.get("type") != Some(&Value::String("null".to_string())))
+                .cloned();
+
+            Some(CommandSchema {
+                name: name.to_string(),
+                description: variant
+                    .get("description")
+                    .and_then(|d| d.as_str())
+                    .unwrap_or_default()
+                    .to_string(),
+                data,
+            })
+        })
+        .collect()
+});
+
+/// Validators of the `data` field, by command name
+static DATA_VALIDATORS: Lazy<HashMap<&'static str, JSONSchema>> = Lazy::new(|| {
+    COMMAND_SCHEMAS
+        .iter()
+        .filter_map(|command| {
+            let validator = JSONSchema::compile(command.data.as_ref()?)
+                .expect("The command data schemas should be valid");
+            Some((command.name.as_str(), validator))
+        })
+        .collect()
+});
+
+static COMMAND_VALIDATOR: Lazy<JSONSchema> = Lazy::new(|| {
+    let mut schema = schema_of::<CommandObject>();
+
+    // Commands without data can omit the field entirely, like `{ "type": "Stop" }`
+    if let Some(variants) = schema
+        .pointer_mut("/properties/cmd/oneOf")
+        .and_then(|v| v.as_array_mut())
+    {
+        for variant in variants {
+            if variant.pointer("/properties/data/type") == Some(&Value::String("null".to_string()))
+            {
+                if let Some(required) = variant.get_mut("required").and_then(|r| r.as_array_mut()) {
+                    required.retain(|field| field != "data");
+                }
+            }
+        }
+    }
+
+    JSONSchema::compile(&schema).expect("The CommandObject schema should be valid")
+});
+
+/// Generates the JSON Schema of a type with every subschema inlined
+pub fn schema_of<T: JsonSchema>() -> Value {
+    let schema = SchemaSettings::draft07()
+        .with(|settings| settings.inline_subschemas = true)
+        .into_generator()
+        .into_root_schema_for::<T>();
+
+    serde_json::to_value(schema).unwrap_or_default()
+}
+
+/// Names of every command the brain can send
+pub fn command_names() -> impl Iterator<Item = &'static str> {
+    COMMAND_SCHEMAS.iter().map(|command| command.name.as_str())
+}
+
+/// Commands of a module, or the base commands if `module` is `None`
+pub fn command_schemas(module: Option<Module>) -> impl Iterator<Item = &'static CommandSchema> {
+    COMMAND_SCHEMAS
+        .iter()
+        .filter(move |command| Module::of(&command.name) == module)
+}
+
+/// Checks a command object against the schema, returning every violation found
+pub fn validate_command(command: &Value) -> Result<(), String> {
+    let name = command
+        .pointer("/cmd/type")
+        .and_then(|t| t.as_str())
+        .unwrap_or_default();
+
+    if !COMMAND_SCHEMAS.iter().any(|c| c.name == name) {
+        return Err(format!(
+            "/cmd/type: unknown command type \"{name}\". Valid types are: {}",
+            COMMAND_SCHEMAS
+                .iter()
+                .map(|c| c.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+
+    // Validating the data alone gives better errors than the `oneOf` of the whole command
+    if let Some(validator) = DATA_VALIDATORS.get(name) {
+        let data = command.pointer("/cmd/data").unwrap_or(&Value::Null);
+
+        validator.validate(data).map_err(|errors| {
+            errors
+                .map(|error| format!("/cmd/data{}: {}", error.instance_path, error))
+                .collect::<Vec<_>>()
+                .join("\n")
+        })?;
+    }
+
+    COMMAND_VALIDATOR.validate(command).map_err(|errors| {
+        errors
+            .map(|error| format!("{}: {}", error.instance_path, error))
+            .collect::<Vec<_>>()
+            .join("\n")
+    })
+}
+
+/// Documents the commands in the same notation used by the prompts
+pub fn commands_prompt(module: Option<Module>) -> String {
+    let commands = command_schemas(module).collect::<Vec<_>>();
+
+    let types = commands
+        .iter()
+        .map(|command| format!("\"{}\"", command.name))
+        .collect::<Vec<_>>()
+        .join("|");
+
+    let data = commands
+        .iter()
+        .map(|command| {
+            let data = command
+                .data
+                .as_ref()
+                .map(render_schema)
+                .unwrap_or("undefined".to_string());
+
+            let name = match command.name.ends_with("Data") {
+                true => command.name.clone(),
+                false => format!("{}Data", command.name),
+            };
+
+            format!("{name} = {data} // {}", command.description)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let mut prompt = format!("Types: {types}\n\nData:\n{data}");
+    match module {
+        None => prompt += &format!("\n\n{}", type_prompt::<ChannelRepresentation>("Channel")),
+        Some(Module::Members) => prompt += &format!("\n\n{}", type_prompt::<MemberData>("Member")),
+        Some(Module::Roles) => {
+            prompt += &format!("\n\n{}", type_prompt::<RoleRepresentation>("Role"))
+        }
+        _ => {}
+    }
+
+    prompt
+}
+
+pub fn type_prompt<T: JsonSchema>(name: &str) -> String {
+    format!("{name} = {}", render_schema(&schema_of::<T>()))
+}
+
+/// Renders a schema as a TypeScript-like type, e.g. `{ id: u64, category?: string|null }`
+fn render_schema(schema: &Value) -> String {
+    if let Some(values) = schema.get("enum").and_then(|e| e.as_array()) {
+        return values
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<_>>()
+            .join("|");
+    }
+
+    if let Some(variants) = schema
+        .get("anyOf")
+        .or(schema.get("oneOf"))
+        .and_then(|v| v.as_array())
+    {
+        return variants
+            .iter()
+            .map(render_schema)
+            .collect::<Vec<_>>()
+            .join("|");
+    }
+
+    let types = match schema.get("type") {
+        Some(Value::String(ty)) => vec![ty.as_str()],
+        Some(Value::Array(types)) => types.iter().filter_map(|t| t.as_str()).collect(),
+        _ => vec![],
+    };
+
+    types
+        .iter()
+        .map(|ty| match *ty {
+            "object" => render_object(schema),
+            "array" => format!(
+                "[{}]",
+                schema.get("items").map(render_schema).unwrap_or_default()
+            ),
+            "integer" => match schema.get("format").and_then(|f| f.as_str()) {
+                Some("uint64") => "u64".to_string(),
+                Some("uint32") => "u32".to_string(),
+                Some("uint16") => "u16".to_string(),
+                Some("int32") => "i32".to_string(),
+                _ => "i64".to_string(),
+            },
+            "number" => "f64".to_string(),
+            "boolean" => "bool".to_string(),
+            ty => ty.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("|")
+}
+
+fn render_object(schema: &Value) -> String {
+    let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) else {
+        return "object".to_string();
+    };
+    let required = schema
+        .get("required")
+        .and_then(|r| r.as_array())
+        .cloned()
+        .unwrap_or_default();
+
+    let fields = properties
+        .iter()
+        .map(|(name, property)| {
+            let optional = if required.contains(&Value::String(name.clone())) {
+                ""
+            } else {
+                "?"
+            };
+            let comment = property
+                .get("description")
+                .and_then(|d| d.as_str())
+                .map(|d| format!(" /* {d} */"))
+                .unwrap_or_default();
+
+            format!("{name}{optional}: {}{comment}", render_schema(property))
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    format!("{{ {fields} }}")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn valid_commands_pass() {
+        let command = json!({
+            "reasoning": "O canal não é mais usado",
+            "cmd": { "type": "DeleteChannel", "data": { "channel_id": 1 } }
+        });
+
+        assert_eq!(validate_command(&command), Ok(()));
+        assert_eq!(
+            validate_command(&json!({ "reasoning": "Fim", "cmd": { "type": "Stop" } })),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn unknown_command_types_are_errors() {
+        let command = json!({ "reasoning": "", "cmd": { "type": "Explode", "data": null } });
+        let error = validate_command(&command).unwrap_err();
+
+        assert!(error.starts_with("/cmd/type: unknown command type \"Explode\""));
+        assert!(error.contains("DeleteChannel"));
+    }
+
+    #[test]
+    fn missing_fields_are_errors() {
+        let command = json!({ "reasoning": "", "cmd": { "type": "DeleteChannel", "data": {} } });
+        let error = validate_command(&command).unwrap_err();
+
+        assert!(error.starts_with("/cmd/data"));
+        assert!(error.contains("channel_id"));
+    }
+}
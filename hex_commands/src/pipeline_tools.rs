@@ -0,0 +1,64 @@
+use hex_ai::common::{ToolCall, ToolDefinition};
+use serde_json::{json, Value};
+
+use crate::{
+    command_pipeline::Module,
+    pipeline_schema::{command_schemas, CommandSchema},
+};
+
+pub const TOOLS_PROMPT: &str = "You have tools: every command type is a tool. Call exactly ONE tool per response. The tool input is the command data plus the `reasoning` field.";
+
+fn tool(command: &CommandSchema) -> ToolDefinition {
+    let mut input_schema = command
+        .data
+        .clone()
+        .unwrap_or(json!({ "type": "object", "properties": {} }));
+
+    input_schema["properties"]["reasoning"] = json!({
+        "type": "string",
+        "description": "Think about what you should do now and plan the next steps."
+    });
+
+    match input_schema["required"].as_array_mut() {
+        Some(required) => required.insert(0, json!("reasoning")),
+        None => input_schema["required"] = json!(["reasoning"]),
+    }
+
+    ToolDefinition {
+        name: command.name.clone(),
+        description: command.description.clone(),
+        input_schema,
+    }
+}
+
+/// Tools that are always available, even without importing modules
+pub fn base_tools() -> Vec<ToolDefinition> {
+    command_schemas(None).map(tool).collect()
+}
+
+pub fn module_tools(module: Module) -> Vec<ToolDefinition> {
+    command_schemas(Some(module)).map(tool).collect()
+}
+
+/// Converts a tool call into the same command object the model would write as JSON
+pub fn tool_call_to_command(call: ToolCall) -> Value {
+    let mut input = call.input;
+    let reasoning = input
+        .as_object_mut()
+        .and_then(|object| object.remove("reasoning"))
+        .and_then(|reasoning| reasoning.as_str().map(String::from))
+        .unwrap_or_default();
+
+    let data = match input {
+        Value::Object(object) if object.is_empty() => Value::Null,
+        input => input,
+    };
+
+    json!({
+        "reasoning": reasoning,
+        "cmd": {
+            "type": call.name,
+            "data": data
+        }
+    })
+}
@@ -1,8 +1,15 @@
 #![allow(unused_imports)]
 pub use hex_common::*;
 pub use hex_discord::twilight_model::{
-    application::command::CommandOptionType,
-    channel::message::{component::*, *},
+    application::{
+        command::{CommandOptionChoice, CommandOptionType},
+        interaction::application_command::InteractionChannel,
+    },
+    channel::{
+        message::{component::*, *},
+        Attachment, ChannelType,
+    },
+    guild::{Permissions, Role},
     id::{marker::*, *},
     user::*,
 };
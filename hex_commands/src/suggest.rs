@@ -1,15 +1,59 @@
-use hex_discord::twilight_model::channel::ChannelType;
+use hex_discord::twilight_model::guild::Permissions;
 
 use crate::{command_pipeline::*, prelude::*};
 
 #[command("Sugira mudanças e melhorias para o seu servidor atual!")]
 #[name("sugerir")]
+#[guild_only]
+#[cooldown(user, 1, 60)]
+#[cooldown(guild, 10, 3600)]
 pub async fn suggest(
     mut ctx: CommandContext,
     #[rename("sugestão")]
     #[description("A sua sugestão")]
     suggestion: String,
+    #[rename("imagem")]
+    #[description("Uma imagem para a IA analisar junto com a sugestão")]
+    image: Option<Attachment>,
+    #[rename("simular")]
+    #[description("Apenas simula o que a IA faria, sem alterar o servidor (administradores)")]
+    dry_run: Option<bool>,
 ) -> anyhow::Result<()> {
+    let dry_run = dry_run.unwrap_or(false);
+    let is_admin = ctx
+        .interaction
+        .member
+        .as_ref()
+        .and_then(|member| member.permissions)
+        .is_some_and(|permissions| permissions.contains(Permissions::ADMINISTRATOR));
+
+    let valid_image = image.as_ref().is_none_or(|image| {
+        image
+            .content_type
+            .as_ref()
+            .is_some_and(|content_type| content_type.starts_with("image/"))
+    });
+
+    if !valid_image {
+        ctx.reply(
+            Response::from("o anexo precisa ser uma imagem.")
+                .error_response()
+                .set_ephemeral(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    if dry_run && !is_admin {
+        ctx.reply(
+            Response::from("apenas administradores podem simular sugestões.")
+                .error_response()
+                .set_ephemeral(),
+        )
+        .await?;
+        return Ok(());
+    }
+
     let locale = ctx
         .interaction
         .locale
@@ -20,7 +64,6 @@ pub async fn suggest(
         None => return Ok(()),
     };
 
-    let channel_id = channel.id;
     let guild_id = match &channel.guild_id {
         Some(id) => *id,
         None => return Ok(()),
@@ -38,15 +81,15 @@ pub async fn suggest(
         .models()
         .await?;
 
-    let mut pipeline = AiCommandPipeline::new(ctx).await?;
+    let mut pipeline = AiCommandPipeline::new(ctx).await?.set_dry_run(dry_run);
 
     let author_member = db
         .members()
         .get_member(&author.id.to_string(), &guild_id.to_string())
         .await?;
 
-    let command = pipeline
-        .execute_input(InputObject::Suggestion(UserContentData {
+    pipeline
+        .run(InputObject::Suggestion(UserContentData {
             lang: locale,
             user: UserIdentifier {
                 name: author.display_name(),
@@ -55,27 +98,10 @@ pub async fn suggest(
                 notes: author_member.notes,
             },
             content: suggestion,
-            channel: ChannelRepresentation {
-                id: channel_id.get(),
-                name: channel.name.clone().unwrap_or_default(),
-                topic: channel.topic.clone().unwrap_or(String::from("<Empty>")),
-                category: channel.parent_id.and_then(|id| {
-                    channels
-                        .iter()
-                        .find(|c| c.id.get() == id)
-                        .and_then(|c| c.name.clone())
-                }),
-                kind: if channel.kind == ChannelType::GuildCategory {
-                    "Category".to_string()
-                } else {
-                    "Chat".to_string()
-                },
-                message_count: channel.message_count,
-            },
+            channel: ChannelRepresentation::from_channel(&channel, &channels),
+            image_url: image.map(|image| image.url),
         }))
         .await?;
 
-    pipeline.execute(command).await?;
-
     Ok(())
 }
@@ -0,0 +1,76 @@
+use hex_database::bson::oid::ObjectId;
+use hex_discord::twilight_model::guild::Permissions;
+
+use crate::{channel_rollback::*, prelude::*};
+
+#[command("Desfaça as alterações de canais feitas pela IA")]
+#[name("desfazer")]
+#[guild_only]
+#[permissions(MANAGE_CHANNELS)]
+pub async fn undo(
+    mut ctx: CommandContext,
+    #[rename("execução")]
+    #[description("ID da execução (veja em /historico). Padrão: a mais recente")]
+    run_id: Option<String>,
+) -> anyhow::Result<()> {
+    let guild_id = ctx.guild_id()?;
+    let db = ctx.db();
+
+    let run_id = match run_id {
+        Some(run_id) => match ObjectId::parse_str(run_id.trim()) {
+            Ok(run_id) => Some(run_id),
+            Err(_) => {
+                ctx.reply(
+                    Response::from("ID de execução inválido.")
+                        .error_response()
+                        .set_ephemeral(),
+                )
+                .await?;
+                return Ok(());
+            }
+        },
+        None => {
+            db.channel_changes()
+                .find_latest_pending_run(&guild_id.to_string())
+                .await?
+        }
+    };
+
+    let changes = match run_id {
+        Some(run_id) => db.channel_changes().find_pending_by_run(run_id).await?,
+        None => vec![],
+    };
+
+    let (Some(run_id), false) = (run_id, changes.is_empty()) else {
+        ctx.reply(
+            Response::from("não há alterações de canais para desfazer.")
+                .error_response()
+                .set_ephemeral(),
+        )
+        .await?;
+        return Ok(());
+    };
+
+    let author_id = ctx.author_id();
+    let confirmed = ctx
+        .helper()
+        .create_confirmation(
+            author_id,
+            false,
+            format!(
+                "deseja desfazer {} alteração(ões) de canais da execução `{}`?",
+                changes.len(),
+                run_id
+            ),
+        )
+        .await?;
+
+    if !confirmed {
+        return Ok(());
+    }
+
+    let (undone, errors) = undo_run(&ctx.client.http, &db, guild_id, run_id).await?;
+    ctx.reply(undo_result_response(undone, &errors)).await?;
+
+    Ok(())
+}
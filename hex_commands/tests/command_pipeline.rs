@@ -0,0 +1,577 @@
+use std::{sync::Arc, time::Duration};
+
+use hex_ai::{
+    brain::{Brain, BrainParameters},
+    common::{
+        ChatMessage, ChatResponse, Role, ToolCall, ToolChatResponse, ToolChatStream,
+        ToolDefinition, ToolStreamEvent,
+    },
+    scripted_brain::ScriptedBrain,
+};
+use hex_commands::command_pipeline::*;
+use hex_database::{
+    ChannelChangeKind, GuildSettingsModel, MemoryStore, PipelineOutcome, PipelineStepKind,
+};
+use hex_discord::{twilight_model::id::Id, EMBED_FIELD_LIMIT, EMBED_LENGTH_LIMIT};
+use hex_framework::{FakeGuild, GuildOperations};
+
+const GUILD_ID: u64 = 10;
+const AUTHOR_ID: u64 = 1;
+
+/// A guild with a `geral` channel, where the author sends the suggestions
+fn fake_guild() -> FakeGuild {
+    FakeGuild::new(Id::new(GUILD_ID))
+        .add_channel("geral", "Conversas", None)
+        .add_member(AUTHOR_ID, "autor")
+}
+
+/// A pipeline over the default fake guild, answered by the scripted brain
+async fn pipeline(
+    brain: Arc<ScriptedBrain>,
+    settings: GuildSettingsModel,
+) -> (AiCommandPipeline, Arc<MemoryStore>) {
+    detached_pipeline(Arc::new(fake_guild()), brain, settings, false).await
+}
+
+/// A pipeline over the given guild, started from its first channel. Every approval gets `approve`
+async fn detached_pipeline(
+    guild: Arc<FakeGuild>,
+    brain: Arc<ScriptedBrain>,
+    settings: GuildSettingsModel,
+    approve: bool,
+) -> (AiCommandPipeline, Arc<MemoryStore>) {
+    let guild_id = Id::new(GUILD_ID);
+    let store = Arc::new(MemoryStore::new());
+
+    let author = guild
+        .member(guild_id, Id::new(AUTHOR_ID))
+        .await
+        .unwrap()
+        .user;
+    let channel_id = guild.state().channels[0].id;
+
+    let pipeline = AiCommandPipeline::detached(
+        guild,
+        store.clone(),
+        author,
+        guild_id,
+        channel_id,
+        settings,
+        approve,
+    )
+    .set_brain(brain)
+    .set_delay(Duration::ZERO);
+
+    (pipeline, store)
+}
+
+fn settings() -> GuildSettingsModel {
+    GuildSettingsModel::new(GUILD_ID.to_string())
+}
+
+async fn suggestion(pipeline: &AiCommandPipeline, content: &str) -> InputObject {
+    let channels = pipeline.guild.channels(pipeline.guild_id).await.unwrap();
+    let channel = channels
+        .iter()
+        .find(|c| c.id == pipeline.channel_id)
+        .unwrap();
+
+    InputObject::Suggestion(UserContentData {
+        lang: "pt-BR".to_string(),
+        user: UserIdentifier {
+            name: pipeline.author.name.clone(),
+            uid: pipeline.author.id.get(),
+            karma: 0,
+            notes: vec![],
+        },
+        content: content.to_string(),
+        channel: ChannelRepresentation::from_channel(channel, &channels),
+        image_url: None,
+    })
+}
+
+/// The last message of every prompt the brain received
+fn last_messages(brain: &ScriptedBrain) -> Vec<String> {
+    brain
+        .received()
+        .into_iter()
+        .map(|messages| messages.last().unwrap().content.clone())
+        .collect()
+}
+
+const STOP: &str = r#"{"reasoning": "Nada a fazer", "cmd": {"type": "Stop"}}"#;
+
+#[tokio::test]
+async fn empty_and_undefined_data_are_read_as_null() {
+    let brain = Arc::new(ScriptedBrain::new([
+        r#"{"reasoning": "Respondendo", "cmd": {"type": "GetRoleList", "data": {}}}"#,
+        r#"{"reasoning": "Nada a fazer", "cmd": {"type": "Stop", "data": undefined}}"#,
+    ]));
+    let (mut pipeline, _) = pipeline(brain.clone(), settings()).await;
+
+    let input = suggestion(&pipeline, "Quais cargos existem?").await;
+    let command = pipeline.execute_input(input).await.unwrap();
+
+    assert_eq!(command.cmd, CommandType::GetRoleList(None));
+    assert_eq!(pipeline.error_counter, 0);
+
+    let input = InputObject::SystemError("Continue".to_string());
+    let command = pipeline.execute_input(input).await.unwrap();
+
+    assert_eq!(command.cmd, CommandType::Stop(None));
+    assert_eq!(pipeline.error_counter, 0);
+}
+
+#[tokio::test]
+async fn malformed_output_is_retried_with_the_error() {
+    let brain = Arc::new(ScriptedBrain::new([
+        "Claro! Vou parar agora.",
+        r#"{"reasoning": "Nada a fazer", "cmd": {"type": "Fly"}}"#,
+        STOP,
+    ]));
+    let (mut pipeline, store) = pipeline(brain.clone(), settings()).await;
+
+    let input = suggestion(&pipeline, "Pare").await;
+    pipeline.run(input).await.unwrap();
+
+    assert_eq!(pipeline.error_counter, 2);
+    assert_eq!(brain.remaining_responses(), 0);
+
+    let prompts = last_messages(&brain);
+    assert_eq!(prompts.len(), 3);
+    assert!(prompts[1].contains("<JSON Error>"));
+    assert!(prompts[2].contains("<JSON Error>"));
+
+    let run = &store.state().runs[0];
+    assert_eq!(run.outcome, PipelineOutcome::Completed);
+    assert_eq!(
+        run.steps
+            .iter()
+            .filter(|step| step.kind == PipelineStepKind::MalformedCommand)
+            .count(),
+        2
+    );
+}
+
+#[tokio::test]
+async fn too_many_errors_stop_the_run() {
+    let brain = Arc::new(ScriptedBrain::new(["nope", "nope", "nope", "nope", STOP]));
+    let (mut pipeline, store) = pipeline(brain.clone(), settings()).await;
+
+    let input = suggestion(&pipeline, "Pare").await;
+    assert!(pipeline.run(input).await.is_err());
+
+    assert_eq!(pipeline.error_counter, 4);
+    assert!(!pipeline.active);
+    assert_eq!(brain.remaining_responses(), 1);
+    assert!(matches!(
+        store.state().runs[0].outcome,
+        PipelineOutcome::Failed(_)
+    ));
+}
+
+#[tokio::test]
+async fn command_errors_count_towards_the_limit() {
+    let brain = Arc::new(ScriptedBrain::new([
+        r#"{"reasoning": "a", "cmd": {"type": "ImportModule", "data": {"module_name": "nope"}}}"#,
+        r#"{"reasoning": "b", "cmd": {"type": "ImportModule", "data": {"module_name": "nope"}}}"#,
+        r#"{"reasoning": "c", "cmd": {"type": "ImportModule", "data": {"module_name": "nope"}}}"#,
+        r#"{"reasoning": "d", "cmd": {"type": "ImportModule", "data": {"module_name": "nope"}}}"#,
+        STOP,
+    ]));
+    let (mut pipeline, _) = pipeline(brain.clone(), settings()).await;
+
+    let input = suggestion(&pipeline, "Importe algo").await;
+    let error = pipeline.run(input).await.unwrap_err();
+
+    assert!(error.to_string().contains("Invalid module name: nope"));
+    assert_eq!(pipeline.error_counter, 4);
+    assert_eq!(brain.remaining_responses(), 1);
+}
+
+#[tokio::test]
+async fn importing_a_module_sends_its_prompt() {
+    let brain = Arc::new(ScriptedBrain::new([
+        r#"{"reasoning": "Preciso dos canais", "cmd": {"type": "ImportModule", "data": {"module_name": "Channels"}}}"#,
+        STOP,
+    ]));
+    let (mut pipeline, _) = pipeline(brain.clone(), settings()).await;
+
+    let input = suggestion(&pipeline, "Crie um canal").await;
+    pipeline.run(input).await.unwrap();
+
+    assert!(pipeline.imported_modules.contains(&Module::Channels));
+    assert_eq!(pipeline.error_counter, 0);
+    assert!(last_messages(&brain)[1].contains("Successfuly imported module channels"));
+}
+
+#[tokio::test]
+async fn importing_twice_is_an_error() {
+    let import = r#"{"reasoning": "Preciso dos canais", "cmd": {"type": "ImportModule", "data": {"module_name": "channels"}}}"#;
+    let brain = Arc::new(ScriptedBrain::new([import, import, STOP]));
+    let (mut pipeline, _) = pipeline(brain.clone(), settings()).await;
+
+    let input = suggestion(&pipeline, "Crie um canal").await;
+    pipeline.run(input).await.unwrap();
+
+    assert_eq!(pipeline.error_counter, 1);
+    assert!(last_messages(&brain)[2].contains("Module is already imported: channels"));
+}
+
+#[tokio::test]
+async fn disabled_modules_cannot_be_imported() {
+    let brain = Arc::new(ScriptedBrain::new([
+        r#"{"reasoning": "Preciso dos canais", "cmd": {"type": "ImportModule", "data": {"module_name": "channels"}}}"#,
+        STOP,
+    ]));
+    let mut settings = settings();
+    settings.enabled_modules = Some(vec!["members".to_string()]);
+    let (mut pipeline, _) = pipeline(brain.clone(), settings).await;
+
+    let input = suggestion(&pipeline, "Crie um canal").await;
+    pipeline.run(input).await.unwrap();
+
+    assert!(pipeline.imported_modules.is_empty());
+    assert!(last_messages(&brain)[1]
+        .contains("Invalid module name: channels. Valid modules are: members"));
+}
+
+#[tokio::test]
+async fn module_commands_require_the_import() {
+    let brain = Arc::new(ScriptedBrain::new([
+        r#"{"reasoning": "Vendo os canais", "cmd": {"type": "GetChannelList"}}"#,
+        STOP,
+    ]));
+    let (mut pipeline, _) = pipeline(brain.clone(), settings()).await;
+
+    let input = suggestion(&pipeline, "Quais canais existem?").await;
+    pipeline.run(input).await.unwrap();
+
+    assert_eq!(pipeline.error_counter, 1);
+    assert!(last_messages(&brain)[1]
+        .contains("GetChannelList is a command of the channels module. Import the module first"));
+}
+
+fn import(module: &str) -> String {
+    format!(
+        r#"{{"reasoning": "Importando", "cmd": {{"type": "ImportModule", "data": {{"module_name": "{module}"}}}}}}"#
+    )
+}
+
+#[tokio::test]
+async fn channels_are_created_in_a_new_category() {
+    let brain = Arc::new(ScriptedBrain::new([
+        import("channels"),
+        r#"{"reasoning": "Criando o canal", "cmd": {"type": "CreateChannel", "data": {"channel_name": "ideias", "topic": "Ideias do servidor", "category": "Projetos", "kind": "Chat"}}}"#.to_string(),
+        STOP.to_string(),
+    ]));
+    let guild = Arc::new(fake_guild());
+    let (mut pipeline, store) =
+        detached_pipeline(guild.clone(), brain.clone(), settings(), false).await;
+
+    let input = suggestion(&pipeline, "Crie um canal de ideias em Projetos").await;
+    pipeline.run(input).await.unwrap();
+
+    let channels = guild.state().channels;
+    let category = channels
+        .iter()
+        .find(|c| c.name.as_deref() == Some("Projetos"))
+        .unwrap();
+    let channel = channels
+        .iter()
+        .find(|c| c.name.as_deref() == Some("ideias"))
+        .unwrap();
+    assert_eq!(channel.parent_id, Some(category.id));
+    assert_eq!(channel.topic.as_deref(), Some("Ideias do servidor"));
+
+    let state = store.state();
+    assert_eq!(pipeline.channel_changes, 2);
+    assert_eq!(state.channel_changes.len(), 2);
+    assert!(state
+        .channel_changes
+        .iter()
+        .all(|change| change.kind == ChannelChangeKind::Created && change.before.is_none()));
+
+    let run = &state.runs[0];
+    assert_eq!(run.outcome, PipelineOutcome::Completed);
+    assert_eq!(run.steps, pipeline.run.steps);
+}
+
+/// Deletes the `lixo` channel, with the given moderator answer
+async fn delete_channel(approve: bool) -> (Arc<FakeGuild>, Arc<MemoryStore>, Arc<ScriptedBrain>) {
+    let guild = Arc::new(fake_guild().add_channel("lixo", "Canal antigo", None));
+    let channel_id = guild.state().channels[1].id;
+
+    let brain = Arc::new(ScriptedBrain::new([
+        import("channels"),
+        format!(
+            r#"{{"reasoning": "Apagando", "cmd": {{"type": "DeleteChannel", "data": {{"channel_id": {channel_id}}}}}}}"#
+        ),
+        STOP.to_string(),
+    ]));
+    let (mut pipeline, store) =
+        detached_pipeline(guild.clone(), brain.clone(), settings(), approve).await;
+
+    let input = suggestion(&pipeline, "Apague o canal lixo").await;
+    pipeline.run(input).await.unwrap();
+
+    (guild, store, brain)
+}
+
+#[tokio::test]
+async fn approved_deletes_remove_the_channel() {
+    let (guild, store, _) = delete_channel(true).await;
+
+    assert!(!guild
+        .state()
+        .channels
+        .iter()
+        .any(|c| c.name.as_deref() == Some("lixo")));
+
+    let changes = store.state().channel_changes;
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].kind, ChannelChangeKind::Deleted);
+    assert_eq!(changes[0].before.as_ref().unwrap().name, "lixo");
+}
+
+#[tokio::test]
+async fn rejected_deletes_keep_the_channel() {
+    let (guild, store, brain) = delete_channel(false).await;
+
+    assert!(guild
+        .state()
+        .channels
+        .iter()
+        .any(|c| c.name.as_deref() == Some("lixo")));
+    assert!(store.state().channel_changes.is_empty());
+    assert!(last_messages(&brain)[2].contains("Rejected by moderator"));
+}
+
+#[tokio::test]
+async fn warnings_and_karma_are_stored() {
+    let brain = Arc::new(ScriptedBrain::new([
+        import("moderation"),
+        import("members"),
+        r#"{"reasoning": "Avisando", "cmd": {"type": "WarnMember", "data": {"user_id": 2, "reason": "Spam no geral"}}}"#.to_string(),
+        r#"{"reasoning": "Recompensando", "cmd": {"type": "AddKarma", "data": {"user_id": 2, "amount": 5, "reason": "Ajudou alguém"}}}"#.to_string(),
+        r#"{"reasoning": "Conferindo", "cmd": {"type": "GetMemberData", "data": {"idfilter": 2}}}"#.to_string(),
+        STOP.to_string(),
+    ]));
+    let guild = Arc::new(fake_guild().add_member(2, "alvo"));
+    let (mut pipeline, store) = detached_pipeline(guild, brain.clone(), settings(), false).await;
+
+    let input = suggestion(&pipeline, "Avise o alvo e dê karma a ele").await;
+    pipeline.run(input).await.unwrap();
+
+    assert_eq!(pipeline.error_counter, 0);
+
+    let members = store.state().members;
+    let member = members.iter().find(|m| m.user_id == "2").unwrap();
+    assert_eq!(member.guild_id, GUILD_ID.to_string());
+    assert_eq!(member.karma, 5);
+    assert_eq!(member.warnings.len(), 1);
+    assert_eq!(member.warnings[0].reason, "Spam no geral");
+    assert_eq!(member.warnings[0].requested_by, Some(AUTHOR_ID.to_string()));
+
+    let member_data = &last_messages(&brain)[5];
+    assert!(member_data.contains(r#"\"karma\": 5"#));
+    assert!(member_data.contains(r#"\"warning_count\": 1"#));
+}
+
+#[tokio::test]
+async fn replies_are_kept_in_a_detached_origin() {
+    let brain = Arc::new(ScriptedBrain::new([
+        r#"{"reasoning": "Respondendo", "cmd": {"type": "SendReply", "data": {"content": "Olá!"}}}"#,
+        STOP,
+    ]));
+    let (mut pipeline, _) = pipeline(brain, settings()).await;
+
+    let input = suggestion(&pipeline, "Oi").await;
+    pipeline.run(input).await.unwrap();
+
+    let PipelineOrigin::Detached { replies, .. } = &pipeline.origin else {
+        panic!("Expected a detached origin");
+    };
+    assert_eq!(replies.len(), 1);
+    assert_eq!(replies[0].content.as_deref(), Some("Olá!"));
+}
+
+#[tokio::test]
+async fn dry_runs_only_plan_the_changes() {
+    let brain = Arc::new(ScriptedBrain::new([
+        import("channels"),
+        r#"{"reasoning": "Criando o canal", "cmd": {"type": "CreateChannel", "data": {"channel_name": "ideias", "topic": "Ideias", "category": null, "kind": "Chat"}}}"#.to_string(),
+        STOP.to_string(),
+    ]));
+    let guild = Arc::new(fake_guild());
+    let (pipeline, store) = detached_pipeline(guild.clone(), brain, settings(), false).await;
+    let mut pipeline = pipeline.set_dry_run(true);
+
+    let input = suggestion(&pipeline, "Crie um canal de ideias").await;
+    pipeline.run(input).await.unwrap();
+
+    assert_eq!(guild.state().channels.len(), 1);
+    assert!(store.state().channel_changes.is_empty());
+    assert_eq!(pipeline.plan.len(), 1);
+    assert!(store.state().runs[0].dry_run);
+
+    let PipelineOrigin::Detached { replies, .. } = &pipeline.origin else {
+        panic!("Expected a detached origin");
+    };
+    assert_eq!(replies.len(), 1);
+}
+
+#[tokio::test]
+async fn failed_dry_runs_still_show_the_partial_plan() {
+    let create = format!(
+        r#"{{"reasoning": "{}", "cmd": {{"type": "CreateChannel", "data": {{"channel_name": "ideias", "topic": "{}", "category": null, "kind": "Chat"}}}}}}"#,
+        "r".repeat(500),
+        "t".repeat(500)
+    );
+    let mut script = vec![import("channels")];
+    script.extend(std::iter::repeat_n(create, 30));
+    script.extend(std::iter::repeat_n("nope".to_string(), 4));
+
+    let brain = Arc::new(ScriptedBrain::new(script));
+    let (pipeline, _) = pipeline(brain, settings()).await;
+    let mut pipeline = pipeline.set_dry_run(true);
+
+    let input = suggestion(&pipeline, "Crie muitos canais").await;
+    assert!(pipeline.run(input).await.is_err());
+    assert_eq!(pipeline.plan.len(), 30);
+
+    let PipelineOrigin::Detached { replies, .. } = &pipeline.origin else {
+        panic!("Expected a detached origin");
+    };
+    let embed = replies[0].embeds.as_ref().unwrap()[0].clone();
+    assert!(embed.length() <= EMBED_LENGTH_LIMIT);
+    assert!(embed.field_count() <= EMBED_FIELD_LIMIT);
+
+    let embed = embed.build();
+    assert_eq!(
+        embed.title.as_deref(),
+        Some("Plano de execução parcial (simulação)")
+    );
+    assert!(embed.description.unwrap().contains("A execução falhou"));
+}
+
+/// A tool-calling brain whose first stream breaks in the middle. Then it stops
+#[derive(Debug, Default)]
+struct BrokenStreamBrain {
+    prompts: std::sync::Mutex<Vec<Vec<ChatMessage>>>,
+}
+
+#[async_trait::async_trait]
+impl Brain for BrokenStreamBrain {
+    fn api_key(&self, _debug: bool) -> String {
+        String::new()
+    }
+
+    async fn prompt_chat(
+        &self,
+        _params: BrainParameters,
+        _messages: Vec<ChatMessage>,
+    ) -> anyhow::Result<ChatResponse> {
+        anyhow::bail!("Only tools are supported")
+    }
+
+    fn supports_tools(&self) -> bool {
+        true
+    }
+
+    async fn prompt_tools_stream(
+        &self,
+        _params: BrainParameters,
+        messages: Vec<ChatMessage>,
+        _tools: Vec<ToolDefinition>,
+    ) -> anyhow::Result<ToolChatStream> {
+        let mut prompts = self.prompts.lock().unwrap();
+        prompts.push(messages);
+
+        let events = if prompts.len() == 1 {
+            vec![
+                Ok(ToolStreamEvent::InputDelta(
+                    r#"{"reasoning": "Cri"#.to_string(),
+                )),
+                Err(anyhow::anyhow!("The tool input was cut")),
+            ]
+        } else {
+            vec![Ok(ToolStreamEvent::Done(ToolChatResponse {
+                message: ChatMessage {
+                    role: Role::Assistant,
+                    content: String::new(),
+                    image_url: None,
+                },
+                tool_calls: vec![ToolCall {
+                    name: "Stop".to_string(),
+                    input: serde_json::json!({ "reasoning": "Nada a fazer" }),
+                }],
+            }))]
+        };
+
+        Ok(Box::pin(tokio_stream::iter(events)))
+    }
+}
+
+#[tokio::test]
+async fn broken_streams_are_retried_as_malformed_commands() {
+    let brain = Arc::new(BrokenStreamBrain::default());
+    let (pipeline, store) = pipeline(Arc::new(ScriptedBrain::default()), settings()).await;
+    let mut pipeline = pipeline.set_brain(brain.clone());
+
+    let input = suggestion(&pipeline, "Pare").await;
+    pipeline.run(input).await.unwrap();
+
+    assert_eq!(pipeline.error_counter, 1);
+
+    let prompts = brain.prompts.lock().unwrap();
+    assert_eq!(prompts.len(), 2);
+    let retry = &prompts[1];
+    assert_eq!(
+        retry[retry.len() - 2].content,
+        r#"{"reasoning": "Cri"#.to_string()
+    );
+    assert!(retry[retry.len() - 1]
+        .content
+        .contains("The tool input was cut"));
+
+    assert_eq!(store.state().runs[0].outcome, PipelineOutcome::Completed);
+}
+
+#[tokio::test]
+async fn zero_ids_are_command_errors() {
+    let brain = Arc::new(ScriptedBrain::new([
+        import("moderation"),
+        r#"{"reasoning": "Avisando", "cmd": {"type": "WarnMember", "data": {"user_id": 0, "reason": "Spam"}}}"#.to_string(),
+        r#"{"reasoning": "Silenciando", "cmd": {"type": "TimeoutMember", "data": {"user_id": 0, "duration_minutes": 5, "reason": "Spam"}}}"#.to_string(),
+        STOP.to_string(),
+    ]));
+    let (mut pipeline, _) = pipeline(brain.clone(), settings()).await;
+
+    let input = suggestion(&pipeline, "Avise o usuário 0").await;
+    pipeline.run(input).await.unwrap();
+
+    assert_eq!(pipeline.error_counter, 2);
+    assert!(last_messages(&brain)[2].contains("Invalid ID: 0"));
+    assert!(last_messages(&brain)[3].contains("Invalid ID: 0"));
+}
+
+#[tokio::test]
+async fn zero_role_and_member_ids_are_command_errors() {
+    let brain = Arc::new(ScriptedBrain::new([
+        import("roles"),
+        r#"{"reasoning": "Dando o cargo", "cmd": {"type": "AssignRole", "data": {"user_id": 1, "role_id": 0}}}"#.to_string(),
+        r#"{"reasoning": "Dando karma", "cmd": {"type": "AddKarma", "data": {"user_id": 0, "amount": 1, "reason": "Ajuda"}}}"#.to_string(),
+        STOP.to_string(),
+    ]));
+    let (mut pipeline, store) =
+        detached_pipeline(Arc::new(fake_guild()), brain.clone(), settings(), true).await;
+
+    let input = suggestion(&pipeline, "Dê o cargo 0 para o autor").await;
+    pipeline.run(input).await.unwrap();
+
+    assert_eq!(pipeline.error_counter, 2);
+    assert!(last_messages(&brain)[2].contains("Invalid ID: 0"));
+    assert!(last_messages(&brain)[3].contains("Invalid ID: 0"));
+    assert!(store.state().members.is_empty());
+}
@@ -0,0 +1,75 @@
+use bson::{doc, oid::ObjectId};
+use mongodb::{
+    options::{FindOneOptions, FindOptions},
+    Collection,
+};
+
+use crate::{channel_change_model::ChannelChangeModel, common::*, *};
+
+#[allow(unused)]
+pub struct ChannelChangeCommands {
+    pub collection: Collection<ChannelChangeModel>,
+    db: HexDatabase,
+}
+
+impl ChannelChangeCommands {
+    pub const fn new(collection: Collection<ChannelChangeModel>, db: HexDatabase) -> Self {
+        Self { collection, db }
+    }
+
+    pub async fn create(&self, change: &ChannelChangeModel) -> anyhow::Result<()> {
+        self.collection.insert_one(change, None).await?;
+        Ok(())
+    }
+
+    pub async fn save(&self, change: &ChannelChangeModel) -> anyhow::Result<()> {
+        self.collection
+            .replace_one(query_by_id(change.id), change, None)
+            .await?;
+        Ok(())
+    }
+
+    /// Changes of a run that were not undone yet, newest first
+    pub async fn find_pending_by_run(
+        &self,
+        run_id: ObjectId,
+    ) -> anyhow::Result<Vec<ChannelChangeModel>> {
+        let query = doc! {
+            "run_id": run_id,
+            "undone": false
+        };
+
+        let options = FindOptions::builder()
+            .sort(doc! { "created_at": -1 })
+            .build();
+
+        let mut cursor = self.collection.find(query, options).await?;
+        let mut changes = vec![];
+        while cursor.advance().await? {
+            changes.push(cursor.deserialize_current()?);
+        }
+
+        Ok(changes)
+    }
+
+    /// The most recent run of a guild that still has changes to undo
+    pub async fn find_latest_pending_run(
+        &self,
+        guild_id: &str,
+    ) -> anyhow::Result<Option<ObjectId>> {
+        let query = doc! {
+            "guild_id": guild_id,
+            "undone": false
+        };
+
+        let options = FindOneOptions::builder()
+            .sort(doc! { "created_at": -1 })
+            .build();
+
+        Ok(self
+            .collection
+            .find_one(query, options)
+            .await?
+            .map(|change| change.run_id))
+    }
+}
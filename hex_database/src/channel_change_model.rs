@@ -0,0 +1,66 @@
+use bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+use crate::common::DatabaseDateTime;
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PermissionOverwriteSnapshot {
+    pub id: String,
+    pub kind: u8,
+    /// Permission bits, stored as strings since they don't fit in a BSON integer
+    pub allow: String,
+    pub deny: String,
+}
+
+/// State of a channel right before Hex changed it
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ChannelSnapshot {
+    pub name: String,
+    pub topic: Option<String>,
+    pub kind: u8,
+    pub parent_id: Option<String>,
+    pub position: Option<i32>,
+    pub permission_overwrites: Vec<PermissionOverwriteSnapshot>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ChannelChangeKind {
+    Created,
+    Edited,
+    Deleted,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ChannelChangeModel {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub run_id: ObjectId,
+    pub guild_id: String,
+    pub channel_id: String,
+    pub kind: ChannelChangeKind,
+    /// `None` for created channels, which are undone by deleting them
+    pub before: Option<ChannelSnapshot>,
+    pub undone: bool,
+    pub created_at: DatabaseDateTime,
+}
+
+impl ChannelChangeModel {
+    pub fn new(
+        run_id: ObjectId,
+        guild_id: String,
+        channel_id: String,
+        kind: ChannelChangeKind,
+        before: Option<ChannelSnapshot>,
+    ) -> Self {
+        Self {
+            id: ObjectId::new(),
+            run_id,
+            guild_id,
+            channel_id,
+            kind,
+            before,
+            undone: false,
+            created_at: DatabaseDateTime::now(),
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use bson::doc;
+use hex_common::Cache;
+use mongodb::Collection;
+use once_cell::sync::Lazy;
+
+use crate::{common::*, guild_settings_model::GuildSettingsModel, *};
+
+static CACHE_GUILD_ID: Lazy<Cache<String, GuildSettingsModel>> = Lazy::new(|| Cache::new(1000));
+
+#[allow(unused)]
+pub struct GuildSettingsCommands {
+    pub collection: Collection<GuildSettingsModel>,
+    db: HexDatabase,
+}
+
+impl GuildSettingsCommands {
+    pub const fn new(collection: Collection<GuildSettingsModel>, db: HexDatabase) -> Self {
+        Self { collection, db }
+    }
+
+    pub async fn save(&self, settings: &GuildSettingsModel) -> anyhow::Result<()> {
+        CACHE_GUILD_ID.remove(&settings.guild_id);
+
+        self.collection
+            .replace_one(query_by_id(settings.id), settings, None)
+            .await?;
+        Ok(())
+    }
+
+    /// Settings of the guild, created with the defaults on the first access
+    pub async fn get_guild(&self, guild_id: &str) -> anyhow::Result<GuildSettingsModel> {
+        if let Some(settings) = CACHE_GUILD_ID.get_cloned(&guild_id.to_string()) {
+            return Ok(settings);
+        }
+
+        let settings = match self
+            .collection
+            .find_one(doc! { "guild_id": guild_id }, None)
+            .await?
+        {
+            Some(settings) => settings,
+            None => {
+                let settings = GuildSettingsModel::new(guild_id.to_string());
+                self.collection.insert_one(&settings, None).await?;
+                settings
+            }
+        };
+
+        CACHE_GUILD_ID.insert(guild_id.to_string(), settings.clone());
+        Ok(settings)
+    }
+}
@@ -0,0 +1,66 @@
+use bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_KICK_KARMA: i64 = -100;
+pub const DEFAULT_BAN_KARMA: i64 = -200;
+
+/// How the AI pipeline behaves in a guild
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct GuildSettingsModel {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub guild_id: String,
+    /// Brain name, like "claude" or "openai". `None` uses the bot default
+    #[serde(default)]
+    pub brain: Option<String>,
+    /// Model name passed to the brain. `None` uses the brain default
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Modules the pipeline can import. `None` enables every module
+    #[serde(default)]
+    pub enabled_modules: Option<Vec<String>>,
+    /// Karma at which members should be kicked
+    pub kick_karma: i64,
+    /// Karma at which members should be banned
+    pub ban_karma: i64,
+    /// Members with this role can approve destructive commands
+    #[serde(default)]
+    pub moderator_role_id: Option<String>,
+    /// Channel where finished runs are logged
+    #[serde(default)]
+    pub log_channel_id: Option<String>,
+    /// Channel where destructive commands wait for a moderator. `None` uses `MODERATOR_CHANNEL_ID`
+    #[serde(default)]
+    pub approval_channel_id: Option<String>,
+    /// Language Hex answers in, like "pt-BR". `None` keeps the user's language
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Server rules appended to the system prompt
+    #[serde(default)]
+    pub custom_rules: Option<String>,
+}
+
+impl GuildSettingsModel {
+    pub fn new(guild_id: String) -> Self {
+        Self {
+            id: ObjectId::new(),
+            guild_id,
+            brain: None,
+            model: None,
+            enabled_modules: None,
+            kick_karma: DEFAULT_KICK_KARMA,
+            ban_karma: DEFAULT_BAN_KARMA,
+            moderator_role_id: None,
+            log_channel_id: None,
+            approval_channel_id: None,
+            language: None,
+            custom_rules: None,
+        }
+    }
+
+    pub fn is_module_enabled(&self, module: &str) -> bool {
+        self.enabled_modules
+            .as_ref()
+            .is_none_or(|modules| modules.iter().any(|m| m == module))
+    }
+}
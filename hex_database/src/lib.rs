@@ -1,13 +1,32 @@
+mod channel_change_commands;
+mod channel_change_model;
 pub mod common;
+mod guild_settings_commands;
+mod guild_settings_model;
 mod member_commands;
 mod member_model;
+mod memory_store;
+mod pipeline_run_commands;
+mod pipeline_run_model;
+mod pipeline_store;
 
 use std::sync::Arc;
 
 use mongodb::{Client, Database};
 
+use channel_change_commands::ChannelChangeCommands;
+pub use channel_change_model::{
+    ChannelChangeKind, ChannelChangeModel, ChannelSnapshot, PermissionOverwriteSnapshot,
+};
+use guild_settings_commands::GuildSettingsCommands;
+pub use guild_settings_model::{GuildSettingsModel, DEFAULT_BAN_KARMA, DEFAULT_KICK_KARMA};
 use member_commands::MemberCommands;
+pub use member_model::{MemberModel, MemberWarning};
+pub use memory_store::{MemoryStore, MemoryStoreState};
 pub use mongodb::bson;
+use pipeline_run_commands::PipelineRunCommands;
+pub use pipeline_run_model::{PipelineOutcome, PipelineRunModel, PipelineStep, PipelineStepKind};
+pub use pipeline_store::PipelineStore;
 
 #[derive(Debug, Clone)]
 pub enum DatabaseState {
@@ -45,4 +64,19 @@ impl HexDatabase {
         let collection = self.db().collection("members");
         MemberCommands::new(collection, self.clone())
     }
+
+    pub fn guild_settings(&self) -> GuildSettingsCommands {
+        let collection = self.db().collection("guild_settings");
+        GuildSettingsCommands::new(collection, self.clone())
+    }
+
+    pub fn pipeline_runs(&self) -> PipelineRunCommands {
+        let collection = self.db().collection("pipeline_runs");
+        PipelineRunCommands::new(collection, self.clone())
+    }
+
+    pub fn channel_changes(&self) -> ChannelChangeCommands {
+        let collection = self.db().collection("channel_changes");
+        ChannelChangeCommands::new(collection, self.clone())
+    }
 }
@@ -1,6 +1,16 @@
 use bson::oid::ObjectId;
 use serde::{Deserialize, Serialize};
 
+use crate::common::DatabaseDateTime;
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct MemberWarning {
+    pub reason: String,
+    /// The user whose suggestion or message led to the warning
+    pub requested_by: Option<String>,
+    pub created_at: DatabaseDateTime,
+}
+
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct MemberModel {
     #[serde(rename = "_id")]
@@ -9,6 +19,8 @@ pub struct MemberModel {
     pub guild_id: String,
     pub karma: i64,
     pub notes: Vec<String>,
+    #[serde(default)]
+    pub warnings: Vec<MemberWarning>,
 }
 
 impl MemberModel {
@@ -19,6 +31,7 @@ impl MemberModel {
             guild_id,
             karma: 0,
             notes: vec![],
+            warnings: vec![],
         }
     }
 
@@ -29,4 +42,12 @@ impl MemberModel {
             self.notes.remove(0);
         }
     }
+
+    pub fn push_warning(&mut self, reason: String, requested_by: Option<String>) {
+        self.warnings.push(MemberWarning {
+            reason,
+            requested_by,
+            created_at: DatabaseDateTime::now(),
+        });
+    }
 }
@@ -0,0 +1,99 @@
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use bson::oid::ObjectId;
+
+use crate::{ChannelChangeModel, MemberModel, PipelineRunModel, PipelineStep, PipelineStore};
+
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStoreState {
+    pub members: Vec<MemberModel>,
+    pub channel_changes: Vec<ChannelChangeModel>,
+    pub runs: Vec<PipelineRunModel>,
+}
+
+/// An in-memory store, used to run the AI pipeline without MongoDB
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    state: Mutex<MemoryStoreState>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A copy of everything stored so far
+    pub fn state(&self) -> MemoryStoreState {
+        self.state.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl PipelineStore for MemoryStore {
+    async fn get_member(&self, user_id: &str, guild_id: &str) -> anyhow::Result<MemberModel> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(member) = state
+            .members
+            .iter()
+            .find(|m| m.user_id == user_id && m.guild_id == guild_id)
+        {
+            return Ok(member.clone());
+        }
+
+        let member = MemberModel::new(user_id.to_string(), guild_id.to_string());
+        state.members.push(member.clone());
+
+        Ok(member)
+    }
+
+    async fn save_member(&self, member: MemberModel) -> anyhow::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        match state
+            .members
+            .iter_mut()
+            .find(|stored| stored.id == member.id)
+        {
+            Some(stored) => *stored = member,
+            None => state.members.push(member),
+        }
+
+        Ok(())
+    }
+
+    async fn create_channel_change(&self, change: &ChannelChangeModel) -> anyhow::Result<()> {
+        self.state
+            .lock()
+            .unwrap()
+            .channel_changes
+            .push(change.clone());
+        Ok(())
+    }
+
+    async fn create_run(&self, run: &PipelineRunModel) -> anyhow::Result<()> {
+        self.state.lock().unwrap().runs.push(run.clone());
+        Ok(())
+    }
+
+    async fn push_run_step(&self, run_id: ObjectId, step: &PipelineStep) -> anyhow::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let run = state
+            .runs
+            .iter_mut()
+            .find(|run| run.id == run_id)
+            .ok_or(anyhow::anyhow!("Unknown pipeline run: {run_id}"))?;
+
+        run.steps.push(step.clone());
+        Ok(())
+    }
+
+    async fn save_run(&self, run: &PipelineRunModel) -> anyhow::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        match state.runs.iter_mut().find(|stored| stored.id == run.id) {
+            Some(stored) => *stored = run.clone(),
+            None => state.runs.push(run.clone()),
+        }
+
+        Ok(())
+    }
+}
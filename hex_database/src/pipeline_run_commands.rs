@@ -0,0 +1,104 @@
+use bson::{doc, oid::ObjectId};
+use mongodb::{options::FindOptions, Collection};
+
+use crate::{
+    common::*,
+    pipeline_run_model::{PipelineRunModel, PipelineStep},
+    *,
+};
+
+#[allow(unused)]
+pub struct PipelineRunCommands {
+    pub collection: Collection<PipelineRunModel>,
+    db: HexDatabase,
+}
+
+impl PipelineRunCommands {
+    pub const fn new(collection: Collection<PipelineRunModel>, db: HexDatabase) -> Self {
+        Self { collection, db }
+    }
+
+    pub async fn create(&self, run: &PipelineRunModel) -> anyhow::Result<()> {
+        self.collection.insert_one(run, None).await?;
+        Ok(())
+    }
+
+    pub async fn save(&self, run: &PipelineRunModel) -> anyhow::Result<()> {
+        self.collection
+            .replace_one(query_by_id(run.id), run, None)
+            .await?;
+        Ok(())
+    }
+
+    /// Appends a step to a run that is already stored, so the trace survives a crash mid-run
+    pub async fn push_step(&self, run_id: ObjectId, step: &PipelineStep) -> anyhow::Result<()> {
+        self.collection
+            .update_one(
+                query_by_id(run_id),
+                doc! {
+                    "$push": {
+                        "steps": bson::to_bson(step)?
+                    }
+                },
+                None,
+            )
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_by_id(&self, id: ObjectId) -> anyhow::Result<Option<PipelineRunModel>> {
+        Ok(self.collection.find_one(query_by_id(id), None).await?)
+    }
+
+    /// Most recent runs of a guild, newest first. Every filter is optional.
+    pub async fn find_recent(
+        &self,
+        guild_id: &str,
+        user_id: Option<&str>,
+        command_type: Option<&str>,
+        since: Option<chrono::DateTime<chrono::Utc>>,
+        limit: i64,
+    ) -> anyhow::Result<Vec<PipelineRunModel>> {
+        let mut query = doc! {
+            "guild_id": guild_id
+        };
+
+        if let Some(user_id) = user_id {
+            query.insert("user_id", user_id);
+        }
+
+        if let Some(command_type) = command_type {
+            query.insert(
+                "steps",
+                doc! {
+                    "$elemMatch": {
+                        "kind": "Command",
+                        "object_type": command_type
+                    }
+                },
+            );
+        }
+
+        if let Some(since) = since {
+            query.insert(
+                "started_at",
+                doc! {
+                    "$gte": bson::DateTime::from_chrono(since)
+                },
+            );
+        }
+
+        let options = FindOptions::builder()
+            .sort(doc! { "started_at": -1 })
+            .limit(limit)
+            .build();
+
+        let mut cursor = self.collection.find(query, options).await?;
+        let mut runs = vec![];
+        while cursor.advance().await? {
+            runs.push(cursor.deserialize_current()?);
+        }
+
+        Ok(runs)
+    }
+}
@@ -0,0 +1,71 @@
+use bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+use crate::common::DatabaseDateTime;
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub enum PipelineStepKind {
+    Input,
+    Command,
+    MalformedCommand,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PipelineStep {
+    pub kind: PipelineStepKind,
+    /// `type` of the input or command object, e.g. "Suggestion" or "CreateChannel"
+    pub object_type: Option<String>,
+    pub reasoning: Option<String>,
+    /// The object serialized as JSON, or the raw model output for malformed commands
+    pub content: String,
+    pub timestamp: DatabaseDateTime,
+    /// How long the brain took to produce this step
+    pub duration_ms: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "status", content = "error")]
+pub enum PipelineOutcome {
+    Running,
+    Completed,
+    Failed(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct PipelineRunModel {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub guild_id: String,
+    pub user_id: String,
+    pub channel_id: Option<String>,
+    pub dry_run: bool,
+    pub steps: Vec<PipelineStep>,
+    pub outcome: PipelineOutcome,
+    pub started_at: DatabaseDateTime,
+    pub finished_at: Option<DatabaseDateTime>,
+}
+
+impl PipelineRunModel {
+    pub fn new(guild_id: String, user_id: String, channel_id: Option<String>) -> Self {
+        Self {
+            id: ObjectId::new(),
+            guild_id,
+            user_id,
+            channel_id,
+            dry_run: false,
+            steps: vec![],
+            outcome: PipelineOutcome::Running,
+            started_at: DatabaseDateTime::now(),
+            finished_at: None,
+        }
+    }
+
+    pub fn push_step(&mut self, step: PipelineStep) {
+        self.steps.push(step);
+    }
+
+    pub fn finish(&mut self, outcome: PipelineOutcome) {
+        self.outcome = outcome;
+        self.finished_at = Some(DatabaseDateTime::now());
+    }
+}
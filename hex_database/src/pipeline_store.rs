@@ -0,0 +1,52 @@
+use std::fmt::Debug;
+
+use async_trait::async_trait;
+use bson::oid::ObjectId;
+
+use crate::{ChannelChangeModel, HexDatabase, MemberModel, PipelineRunModel, PipelineStep};
+
+/// Storage used by the AI pipeline. Implemented by `HexDatabase` over MongoDB
+/// and by `MemoryStore` in memory, so the pipeline can run without a database.
+#[async_trait]
+pub trait PipelineStore: Debug + Send + Sync {
+    /// The data of a guild member, created if the member has none yet
+    async fn get_member(&self, user_id: &str, guild_id: &str) -> anyhow::Result<MemberModel>;
+
+    async fn save_member(&self, member: MemberModel) -> anyhow::Result<()>;
+
+    async fn create_channel_change(&self, change: &ChannelChangeModel) -> anyhow::Result<()>;
+
+    async fn create_run(&self, run: &PipelineRunModel) -> anyhow::Result<()>;
+
+    /// Appends a step to a run that is already stored
+    async fn push_run_step(&self, run_id: ObjectId, step: &PipelineStep) -> anyhow::Result<()>;
+
+    async fn save_run(&self, run: &PipelineRunModel) -> anyhow::Result<()>;
+}
+
+#[async_trait]
+impl PipelineStore for HexDatabase {
+    async fn get_member(&self, user_id: &str, guild_id: &str) -> anyhow::Result<MemberModel> {
+        self.members().get_member(user_id, guild_id).await
+    }
+
+    async fn save_member(&self, member: MemberModel) -> anyhow::Result<()> {
+        self.members().save(member).await
+    }
+
+    async fn create_channel_change(&self, change: &ChannelChangeModel) -> anyhow::Result<()> {
+        self.channel_changes().create(change).await
+    }
+
+    async fn create_run(&self, run: &PipelineRunModel) -> anyhow::Result<()> {
+        self.pipeline_runs().create(run).await
+    }
+
+    async fn push_run_step(&self, run_id: ObjectId, step: &PipelineStep) -> anyhow::Result<()> {
+        self.pipeline_runs().push_step(run_id, step).await
+    }
+
+    async fn save_run(&self, run: &PipelineRunModel) -> anyhow::Result<()> {
+        self.pipeline_runs().save(run).await
+    }
+}
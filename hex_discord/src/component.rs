@@ -1,7 +1,11 @@
 #![allow(unused)]
-use twilight_model::channel::message::{
-    component::{ActionRow, Button, ButtonStyle},
-    Component, ReactionType,
+use serde_json::{json, Value};
+use twilight_model::channel::{
+    message::{
+        component::{ActionRow, Button, ButtonStyle, SelectMenu, SelectMenuOption},
+        Component, ReactionType,
+    },
+    ChannelType,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -74,6 +78,155 @@ impl ButtonBuilder {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectMenuBuilder {
+    pub data: SelectMenu,
+}
+
+impl SelectMenuBuilder {
+    pub fn new(custom_id: impl ToString) -> Self {
+        Self {
+            data: SelectMenu {
+                custom_id: custom_id.to_string(),
+                disabled: false,
+                max_values: None,
+                min_values: None,
+                options: vec![],
+                placeholder: None,
+            },
+        }
+    }
+
+    pub fn set_placeholder(mut self, placeholder: impl ToString) -> Self {
+        self.data.placeholder = Some(placeholder.to_string());
+        self
+    }
+
+    pub fn set_min_max_values(mut self, min: u8, max: u8) -> Self {
+        self.data.min_values = Some(min);
+        self.data.max_values = Some(max);
+        self
+    }
+
+    pub fn set_disabled(mut self, disabled: bool) -> Self {
+        self.data.disabled = disabled;
+        self
+    }
+
+    pub fn add_option(
+        mut self,
+        label: impl ToString,
+        value: impl ToString,
+        description: Option<String>,
+        default: bool,
+    ) -> Self {
+        self.data.options.push(SelectMenuOption {
+            default,
+            description,
+            emoji: None,
+            label: label.to_string(),
+            value: value.to_string(),
+        });
+        self
+    }
+
+    pub fn build(self) -> Component {
+        Component::SelectMenu(self.data)
+    }
+}
+
+/// What an [`AutoSelectMenuBuilder`] lists. Discord fills in the options
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AutoSelectMenuKind {
+    Role,
+    /// Channels of the given types, or every channel if there's none
+    Channel(Vec<ChannelType>),
+}
+
+/// A select menu of the guild roles or channels, without the 25 options limit.
+/// twilight 0.15 only knows select menus with fixed options, so this one is built as JSON.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AutoSelectMenuBuilder {
+    custom_id: String,
+    kind: AutoSelectMenuKind,
+    placeholder: Option<String>,
+    min_values: Option<u8>,
+    max_values: Option<u8>,
+    default_values: Vec<String>,
+}
+
+impl AutoSelectMenuBuilder {
+    pub fn new(custom_id: impl ToString, kind: AutoSelectMenuKind) -> Self {
+        Self {
+            custom_id: custom_id.to_string(),
+            kind,
+            placeholder: None,
+            min_values: None,
+            max_values: None,
+            default_values: vec![],
+        }
+    }
+
+    /// Selects the role or channel with this ID when the menu is shown
+    pub fn add_default_value(mut self, id: impl ToString) -> Self {
+        self.default_values.push(id.to_string());
+        self
+    }
+
+    pub fn set_placeholder(mut self, placeholder: impl ToString) -> Self {
+        self.placeholder = Some(placeholder.to_string());
+        self
+    }
+
+    pub fn set_min_max_values(mut self, min: u8, max: u8) -> Self {
+        self.min_values = Some(min);
+        self.max_values = Some(max);
+        self
+    }
+
+    /// An action row with the select menu, as JSON
+    pub fn build(self) -> Value {
+        let mut menu = json!({
+            "type": match self.kind {
+                AutoSelectMenuKind::Role => 6,
+                AutoSelectMenuKind::Channel(_) => 8,
+            },
+            "custom_id": self.custom_id,
+        });
+
+        if let Some(placeholder) = self.placeholder {
+            menu["placeholder"] = json!(placeholder);
+        }
+        if let Some(min_values) = self.min_values {
+            menu["min_values"] = json!(min_values);
+        }
+        if let Some(max_values) = self.max_values {
+            menu["max_values"] = json!(max_values);
+        }
+        if !self.default_values.is_empty() {
+            let kind = match self.kind {
+                AutoSelectMenuKind::Role => "role",
+                AutoSelectMenuKind::Channel(_) => "channel",
+            };
+            menu["default_values"] = self
+                .default_values
+                .into_iter()
+                .map(|id| json!({ "id": id, "type": kind }))
+                .collect();
+        }
+        if let AutoSelectMenuKind::Channel(kinds) = self.kind {
+            if !kinds.is_empty() {
+                menu["channel_types"] = json!(kinds.into_iter().map(u8::from).collect::<Vec<_>>());
+            }
+        }
+
+        json!({
+            "type": 1,
+            "components": [menu],
+        })
+    }
+}
+
 impl Default for ActionRowBuilder {
     fn default() -> Self {
         Self {
@@ -98,6 +251,11 @@ impl ActionRowBuilder {
         self
     }
 
+    pub fn add_select_menu(mut self, select_menu: SelectMenuBuilder) -> Self {
+        self.data.components.push(select_menu.build());
+        self
+    }
+
     pub fn build(self) -> Component {
         Component::ActionRow(self.data)
     }
@@ -16,6 +16,11 @@ use crate::UserExtension;
 use super::Emoji;
 use hex_common::Color;
 
+/// Discord rejects embeds whose texts add up to more characters than this
+pub const EMBED_LENGTH_LIMIT: usize = 6000;
+/// Discord rejects embeds with more fields than this
+pub const EMBED_FIELD_LIMIT: usize = 25;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct EmbedField {
     pub name: String,
@@ -218,6 +223,32 @@ impl EmbedBuilder {
         self
     }
 
+    pub fn field_count(&self) -> usize {
+        self.embed.fields.len()
+    }
+
+    /// Characters counted towards Discord's limit: title, description, fields, footer and author
+    pub fn length(&self) -> usize {
+        let embed = &self.embed;
+
+        [
+            embed.title.as_deref(),
+            embed.description.as_deref(),
+            embed.footer.as_ref().map(|f| f.text.as_str()),
+            embed.author.as_ref().map(|a| a.name.as_str()),
+        ]
+        .into_iter()
+        .flatten()
+        .chain(
+            embed
+                .fields
+                .iter()
+                .flat_map(|f| [f.name.as_str(), f.value.as_str()]),
+        )
+        .map(|text| text.chars().count())
+        .sum()
+    }
+
     pub fn add_field_with_emoji(self, emoji: Emoji, mut field: EmbedField) -> Self {
         field.name = format!("{} {}", emoji, field.name);
         self.add_field(field)
@@ -1,11 +1,64 @@
-use crate::{CommandBuilder, CommandContext};
-use hex_discord::twilight_model::id::{marker::ApplicationMarker, Id};
+use crate::{CommandBuilder, CommandContext, Cooldown};
+use hex_discord::twilight_model::{
+    application::command::CommandOptionChoice,
+    guild::Permissions,
+    id::{marker::ApplicationMarker, Id},
+};
 
-pub struct CommandConfig;
+/// Requirements checked before a command runs
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommandConfig {
+    /// Permissions the member needs. Also hides the command from members without them
+    pub permissions: Option<Permissions>,
+    /// Whether the command can't be used in DMs
+    pub guild_only: bool,
+    /// Whether the command can only be used in age-restricted channels
+    pub nsfw: bool,
+    /// Whether only the owners of the bot application can use the command
+    pub owner_only: bool,
+    /// Limits on how often the command can be used
+    pub cooldowns: Vec<Cooldown>,
+}
+
+impl CommandConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_permissions(mut self, permissions: Permissions) -> Self {
+        self.permissions = Some(permissions);
+        self
+    }
+
+    pub fn set_guild_only(mut self, guild_only: bool) -> Self {
+        self.guild_only = guild_only;
+        self
+    }
+
+    pub fn set_nsfw(mut self, nsfw: bool) -> Self {
+        self.nsfw = nsfw;
+        self
+    }
+
+    pub fn set_owner_only(mut self, owner_only: bool) -> Self {
+        self.owner_only = owner_only;
+        self
+    }
+
+    pub fn add_cooldown(mut self, cooldown: Cooldown) -> Self {
+        self.cooldowns.push(cooldown);
+        self
+    }
+}
 
 #[async_trait::async_trait]
 pub trait Command {
     fn command_config(&self) -> CommandConfig;
     fn build_command(&self, application_id: Id<ApplicationMarker>) -> CommandBuilder;
     async fn run(&self, ctx: CommandContext) -> anyhow::Result<()>;
+
+    /// Suggests values for the focused option while the user types
+    async fn autocomplete(&self, _ctx: CommandContext) -> anyhow::Result<Vec<CommandOptionChoice>> {
+        Ok(vec![])
+    }
 }
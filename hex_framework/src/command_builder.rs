@@ -3,7 +3,11 @@ use std::collections::HashMap;
 
 use hex_discord::{
     twilight_model::{
-        application::command::{CommandOption, CommandOptionType, CommandType},
+        application::command::{
+            CommandOption, CommandOptionChoice, CommandOptionChoiceValue, CommandOptionType,
+            CommandOptionValue, CommandType,
+        },
+        channel::ChannelType,
         id::{
             marker::{ApplicationMarker, GuildMarker},
             Id,
@@ -12,6 +16,8 @@ use hex_discord::{
     ApiCommand,
 };
 
+use crate::CommandConfig;
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct CommandBuilder {
     pub command: ApiCommand,
@@ -52,6 +58,14 @@ impl CommandBuilder {
         self
     }
 
+    /// Tells Discord about the requirements of the command, so it can hide it where it can't be used
+    pub fn set_config(mut self, config: &CommandConfig) -> Self {
+        self.command.default_member_permissions = config.permissions;
+        self.command.dm_permission = config.guild_only.then_some(false);
+        self.command.nsfw = config.nsfw.then_some(true);
+        self
+    }
+
     pub fn set_options(mut self, options: Vec<CommandOption>) -> Self {
         self.command.options = options;
         self
@@ -97,13 +111,93 @@ impl CommandOptionBuilder {
         self
     }
 
-    pub fn set_min_max_value(mut self, min: u16, max: u16) -> Self {
+    pub fn set_autocomplete(mut self, autocomplete: bool) -> Self {
+        self.option.autocomplete = Some(autocomplete);
+        self
+    }
+
+    /// Length limits of a string option
+    pub fn set_min_max_length(mut self, min: u16, max: u16) -> Self {
         self.option.min_length = Some(min);
         self.option.max_length = Some(max);
         self
     }
 
-    pub fn build(self) -> CommandOption {
+    /// Value limits of an integer option
+    pub fn set_min_max_integer(mut self, min: Option<i64>, max: Option<i64>) -> Self {
+        self.option.min_value = min.map(CommandOptionValue::Integer);
+        self.option.max_value = max.map(CommandOptionValue::Integer);
+        self
+    }
+
+    /// Value limits of a number option
+    pub fn set_min_max_number(mut self, min: Option<f64>, max: Option<f64>) -> Self {
+        self.option.min_value = min.map(CommandOptionValue::Number);
+        self.option.max_value = max.map(CommandOptionValue::Number);
+        self
+    }
+
+    pub fn add_choice(mut self, choice: CommandOptionChoice) -> Self {
         self.option
+            .choices
+            .get_or_insert_with(Vec::new)
+            .push(choice);
+        self
+    }
+
+    pub fn add_string_choice(self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.add_choice(string_choice(name, value))
+    }
+
+    pub fn add_integer_choice(self, name: impl Into<String>, value: i64) -> Self {
+        self.add_choice(CommandOptionChoice {
+            name: name.into(),
+            name_localizations: None,
+            value: CommandOptionChoiceValue::Integer(value),
+        })
+    }
+
+    pub fn add_number_choice(self, name: impl Into<String>, value: f64) -> Self {
+        self.add_choice(CommandOptionChoice {
+            name: name.into(),
+            name_localizations: None,
+            value: CommandOptionChoiceValue::Number(value),
+        })
+    }
+
+    /// Channel types a channel option accepts. Empty accepts every type
+    pub fn set_channel_types(mut self, channel_types: Vec<ChannelType>) -> Self {
+        self.option.channel_types = Some(channel_types);
+        self
+    }
+
+    /// Adds a nested option, used to declare the options of a subcommand
+    pub fn add_option(mut self, option: CommandOptionBuilder) -> Self {
+        self.option
+            .options
+            .get_or_insert_with(Vec::new)
+            .push(option.build());
+        self
+    }
+
+    pub fn build(mut self) -> CommandOption {
+        // Discord rejects `required` on subcommands and groups
+        if matches!(
+            self.option.kind,
+            CommandOptionType::SubCommand | CommandOptionType::SubCommandGroup
+        ) {
+            self.option.required = None;
+        }
+
+        self.option
+    }
+}
+
+/// A choice whose value is a string, like the suggestions of an autocomplete
+pub fn string_choice(name: impl Into<String>, value: impl Into<String>) -> CommandOptionChoice {
+    CommandOptionChoice {
+        name: name.into(),
+        name_localizations: None,
+        value: CommandOptionChoiceValue::String(value.into()),
     }
 }
@@ -1,4 +1,7 @@
-use std::sync::Arc;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use anyhow::Context;
 use hex_database::HexDatabase;
@@ -6,10 +9,11 @@ use hex_discord::{
     application_command::CommandDataOption,
     twilight_http::{client::InteractionClient, Response as ApiResponse},
     twilight_model::{
+        application::command::CommandOptionChoice,
         channel::Message,
         http::{
             attachment::Attachment,
-            interaction::{InteractionResponse, InteractionResponseType},
+            interaction::{InteractionResponse, InteractionResponseData, InteractionResponseType},
         },
         id::{
             marker::{ChannelMarker, GuildMarker, UserMarker},
@@ -19,6 +23,7 @@ use hex_discord::{
     },
     Interaction, InteractionData,
 };
+use tokio_stream::{Stream, StreamExt};
 
 use crate::{watcher::Watcher, *};
 
@@ -88,11 +93,11 @@ impl CommandContext {
         self.interaction.guild_id.context("Expected a Guild")
     }
 
-    pub fn options(&self) -> OptionHandler {
+    pub fn options(&self) -> OptionHandler<'_> {
         OptionHandler { ctx: self }
     }
 
-    pub fn helper(&mut self) -> CommandContextHelper {
+    pub fn helper(&mut self) -> CommandContextHelper<'_> {
         CommandContextHelper { ctx: self }
     }
 
@@ -193,6 +198,31 @@ impl CommandContext {
         Ok(())
     }
 
+    /// Answers an autocomplete interaction. Discord shows at most 25 choices
+    pub async fn reply_autocomplete(
+        &mut self,
+        mut choices: Vec<CommandOptionChoice>,
+    ) -> anyhow::Result<()> {
+        self.already_replied = true;
+        choices.truncate(25);
+
+        self.interaction_client()
+            .create_response(
+                self.interaction.id,
+                &self.interaction.token,
+                &InteractionResponse {
+                    kind: InteractionResponseType::ApplicationCommandAutocompleteResult,
+                    data: Some(InteractionResponseData {
+                        choices: Some(choices),
+                        ..Default::default()
+                    }),
+                },
+            )
+            .await?;
+
+        Ok(())
+    }
+
     pub async fn followup_interaction(
         &mut self,
         response: impl Into<Response>,
@@ -225,6 +255,53 @@ impl CommandContext {
             .await?)
     }
 
+    /// Edits the interaction reply as the stream yields text deltas, returning the full text.
+    /// `render` turns the text received so far into the reply, `None` keeps the current one.
+    /// Edits are at least `interval` apart, so long generations don't hit Discord rate limits.
+    /// Failed edits are only logged, they shouldn't stop the generation.
+    pub async fn stream_interaction_reply<S, F>(
+        &self,
+        mut stream: S,
+        interval: Duration,
+        render: F,
+    ) -> anyhow::Result<String>
+    where
+        S: Stream<Item = anyhow::Result<String>> + Unpin,
+        F: Fn(&str) -> Option<Response>,
+    {
+        let mut text = String::new();
+        let mut last_update = Instant::now();
+        let mut pending = false;
+
+        while let Some(delta) = stream.next().await {
+            text.push_str(&delta?);
+            pending = true;
+
+            if last_update.elapsed() >= interval {
+                if let Some(response) = render(&text) {
+                    self.try_update_interaction_reply(response).await;
+                }
+
+                last_update = Instant::now();
+                pending = false;
+            }
+        }
+
+        if pending {
+            if let Some(response) = render(&text) {
+                self.try_update_interaction_reply(response).await;
+            }
+        }
+
+        Ok(text)
+    }
+
+    async fn try_update_interaction_reply(&self, response: Response) {
+        if let Err(e) = self.update_interaction_reply(response).await {
+            println!("Cannot update the interaction reply: {e}");
+        }
+    }
+
     pub async fn delete_reply_message(&self) -> anyhow::Result<()> {
         self.interaction_client()
             .delete_response(&self.interaction.token)
@@ -251,6 +328,24 @@ impl CommandContext {
         Ok(())
     }
 
+    /// Acknowledges a component interaction, leaving the message to be edited later
+    pub async fn defer_update_message(&mut self) -> anyhow::Result<()> {
+        self.already_replied = true;
+
+        self.interaction_client()
+            .create_response(
+                self.interaction.id,
+                &self.interaction.token,
+                &InteractionResponse {
+                    kind: InteractionResponseType::DeferredUpdateMessage,
+                    data: None,
+                },
+            )
+            .await?;
+
+        Ok(())
+    }
+
     pub async fn update_specific_message(
         &self,
         message: &Message,
@@ -1,14 +1,21 @@
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
 
+use hex_database::HexDatabase;
 use hex_discord::{
     twilight_model::{
         channel::message::{component::ButtonStyle, ReactionType},
-        id::{marker::UserMarker, Id},
+        id::{
+            marker::{ChannelMarker, UserMarker},
+            Id,
+        },
     },
-    ActionRowBuilder, ButtonBuilder, InteractionExtension,
+    ActionRowBuilder, ButtonBuilder, Interaction, InteractionExtension,
 };
 
-use crate::{watcher::WatcherOptions, CommandContext, Response};
+use crate::{
+    watcher::{Watcher, WatcherOptions},
+    CommandContext, HexClient, Response,
+};
 
 pub struct CommandContextHelper<'a> {
     pub ctx: &'a mut CommandContext,
@@ -21,63 +28,52 @@ impl<'a> CommandContextHelper<'a> {
         delete_after_interaction: bool,
         response: impl Into<Response>,
     ) -> anyhow::Result<bool> {
-        let response: Response = response.into();
-
-        let buttons = vec![
-            ButtonBuilder::new()
-                .set_custom_id("yes")
-                .set_style(ButtonStyle::Success)
-                .set_emoji(ReactionType::Unicode {
-                    name: "✔️".into()
-                }),
-            ButtonBuilder::new()
-                .set_custom_id("no")
-                .set_style(ButtonStyle::Danger)
-                .set_emoji(ReactionType::Unicode {
-                    name: "✖️".into()
-                }),
-        ];
-
-        let response =
-            response.set_components(vec![ActionRowBuilder::new().add_buttons(buttons.clone())]);
-
-        let message = self.ctx.send(response.clone()).await?;
+        self.create_filtered_confirmation(
+            None,
+            move |interaction| interaction.author_id() == Some(user_id),
+            Duration::from_secs(30),
+            delete_after_interaction,
+            response,
+        )
+        .await
+    }
+
+    /// Like `create_confirmation`, but any interaction accepted by `filter` can answer it.
+    /// When `channel_id` is set, the confirmation is posted there instead of replying to the interaction.
+    pub async fn create_filtered_confirmation<
+        F: Fn(&Interaction) -> bool + Sync + Send + 'static,
+    >(
+        &mut self,
+        channel_id: Option<Id<ChannelMarker>>,
+        filter: F,
+        timeout: Duration,
+        delete_after_interaction: bool,
+        response: impl Into<Response>,
+    ) -> anyhow::Result<bool> {
+        let response = confirmation_response(response.into());
+
+        let message = match channel_id {
+            Some(channel_id) => {
+                self.ctx
+                    .send_in_specific_channel(channel_id, response.clone())
+                    .await?
+                    .model()
+                    .await?
+            }
+            None => self.ctx.send(response.clone()).await?,
+        };
         let component = self
             .ctx
             .watcher
-            .await_single_component(
-                message.id,
-                move |interaction| interaction.author_id() == Some(user_id),
-                WatcherOptions {
-                    timeout: Duration::from_secs(30),
-                },
-            )
+            .await_single_component(message.id, filter, WatcherOptions { timeout })
             .await;
         let Ok(Some(component)) = component else {
             return Ok(false);
         };
 
-        let data = component.parse_message_component_data()?;
-
         let mut component_context =
             CommandContext::from_with_interaction(self.ctx, Box::new(component));
-        component_context
-            .update_message(
-                response.set_components(vec![ActionRowBuilder::new().add_buttons(
-                    buttons
-                        .into_iter()
-                        .map(|button| {
-                            if button.data.custom_id.as_ref() == Some(&data.custom_id) {
-                                button.set_style(ButtonStyle::Success)
-                            } else {
-                                button.set_style(ButtonStyle::Secondary)
-                            }
-                            .set_disabled(true)
-                        })
-                        .collect(),
-                )]),
-            )
-            .await?;
+        let confirmed = finish_confirmation(&mut component_context, response).await?;
 
         if delete_after_interaction {
             self.ctx
@@ -86,10 +82,95 @@ impl<'a> CommandContextHelper<'a> {
                 .delete_message(message.channel_id, message.id)
                 .await
                 .ok();
-        } else {
+        } else if channel_id.is_none() {
             *self.ctx = component_context;
         }
 
-        Ok(data.custom_id == "yes")
+        Ok(confirmed)
     }
 }
+
+/// Posts a confirmation in a channel when there is no interaction to reply to,
+/// e.g. for conversations started by a message
+pub async fn create_channel_confirmation<F: Fn(&Interaction) -> bool + Sync + Send + 'static>(
+    client: Arc<HexClient>,
+    watcher: Arc<Watcher>,
+    database: Arc<HexDatabase>,
+    channel_id: Id<ChannelMarker>,
+    filter: F,
+    timeout: Duration,
+    response: impl Into<Response>,
+) -> anyhow::Result<bool> {
+    let response = confirmation_response(response.into());
+
+    let message = client
+        .http
+        .create_message(channel_id)
+        .payload_json(&response.clone().to_json())
+        .await?
+        .model()
+        .await?;
+    let component = watcher
+        .await_single_component(message.id, filter, WatcherOptions { timeout })
+        .await;
+    let Ok(Some(component)) = component else {
+        return Ok(false);
+    };
+
+    let mut component_context =
+        CommandContext::new(client, Box::new(component), watcher, database, vec![]);
+    finish_confirmation(&mut component_context, response).await
+}
+
+fn confirmation_buttons() -> Vec<ButtonBuilder> {
+    vec![
+        ButtonBuilder::new()
+            .set_custom_id("yes")
+            .set_style(ButtonStyle::Success)
+            .set_emoji(ReactionType::Unicode {
+                name: "✔️".into()
+            }),
+        ButtonBuilder::new()
+            .set_custom_id("no")
+            .set_style(ButtonStyle::Danger)
+            .set_emoji(ReactionType::Unicode {
+                name: "✖️".into()
+            }),
+    ]
+}
+
+fn confirmation_response(response: Response) -> Response {
+    response.set_components(vec![
+        ActionRowBuilder::new().add_buttons(confirmation_buttons())
+    ])
+}
+
+/// Disables the buttons, highlighting the chosen one. Returns whether "yes" was chosen
+async fn finish_confirmation(
+    component_context: &mut CommandContext,
+    response: Response,
+) -> anyhow::Result<bool> {
+    let data = component_context
+        .interaction
+        .parse_message_component_data()?;
+
+    component_context
+        .update_message(
+            response.set_components(vec![ActionRowBuilder::new().add_buttons(
+                confirmation_buttons()
+                    .into_iter()
+                    .map(|button| {
+                        if button.data.custom_id.as_ref() == Some(&data.custom_id) {
+                            button.set_style(ButtonStyle::Success)
+                        } else {
+                            button.set_style(ButtonStyle::Secondary)
+                        }
+                        .set_disabled(true)
+                    })
+                    .collect(),
+            )]),
+        )
+        .await?;
+
+    Ok(data.custom_id == "yes")
+}
@@ -0,0 +1,151 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use hex_discord::twilight_model::id::{
+    marker::{GuildMarker, UserMarker},
+    Id,
+};
+
+/// Who shares the uses of a [cooldown](Cooldown)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CooldownBucket {
+    /// Each user has their own uses
+    User,
+    /// Everyone in a guild shares the same uses. In DMs, each user has their own
+    Guild,
+    /// Everyone shares the same uses
+    Global,
+}
+
+/// Limits a command to `uses` uses every `per`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cooldown {
+    pub bucket: CooldownBucket,
+    pub uses: u32,
+    pub per: Duration,
+}
+
+impl Cooldown {
+    pub fn new(bucket: CooldownBucket, uses: u32, per: Duration) -> Self {
+        Self { bucket, uses, per }
+    }
+}
+
+/// The command, which of its cooldowns and the user or guild the uses are counted for
+type BucketKey = (String, usize, u64);
+
+/// Recent uses of a bucket, oldest first, and how long each of them counts
+#[derive(Debug)]
+struct RecentUses {
+    per: Duration,
+    used_at: VecDeque<Instant>,
+}
+
+impl RecentUses {
+    /// Forgets the uses that no longer count
+    fn expire(&mut self, now: Instant) {
+        while self
+            .used_at
+            .front()
+            .is_some_and(|used_at| now.duration_since(*used_at) >= self.per)
+        {
+            self.used_at.pop_front();
+        }
+    }
+}
+
+/// Keeps the recent uses of the commands with cooldowns
+#[derive(Debug, Default)]
+pub struct Cooldowns {
+    uses: Mutex<HashMap<BucketKey, RecentUses>>,
+}
+
+impl Cooldowns {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a use of the command, unless one of its cooldowns is exhausted. In that case
+    /// nothing is registered and the time until the command can be used again is returned
+    pub fn try_use(
+        &self,
+        command: &str,
+        cooldowns: &[Cooldown],
+        user_id: Id<UserMarker>,
+        guild_id: Option<Id<GuildMarker>>,
+    ) -> Option<Duration> {
+        if cooldowns.is_empty() {
+            return None;
+        }
+
+        let now = Instant::now();
+        let mut uses = self.uses.lock().unwrap();
+
+        uses.retain(|_, recent| {
+            recent.expire(now);
+            !recent.used_at.is_empty()
+        });
+
+        let keys = cooldowns
+            .iter()
+            .enumerate()
+            .map(|(index, cooldown)| {
+                let id = match cooldown.bucket {
+                    CooldownBucket::User => user_id.get(),
+                    CooldownBucket::Guild => guild_id.map_or(user_id.get(), |id| id.get()),
+                    CooldownBucket::Global => 0,
+                };
+
+                (command.to_string(), index, id)
+            })
+            .collect::<Vec<_>>();
+
+        let mut wait = Duration::ZERO;
+        for (cooldown, key) in cooldowns.iter().zip(&keys) {
+            let Some(recent) = uses.get(key) else {
+                continue;
+            };
+
+            let limit = cooldown.uses.max(1) as usize;
+            if recent.used_at.len() >= limit {
+                // The oldest uses must expire until there is room for a new one
+                let expires_at = recent.used_at[recent.used_at.len() - limit] + cooldown.per;
+                wait = wait.max(expires_at.duration_since(now));
+            }
+        }
+
+        if !wait.is_zero() {
+            return Some(wait);
+        }
+
+        for (cooldown, key) in cooldowns.iter().zip(keys) {
+            uses.entry(key)
+                .or_insert_with(|| RecentUses {
+                    per: cooldown.per,
+                    used_at: VecDeque::new(),
+                })
+                .used_at
+                .push_back(now);
+        }
+
+        None
+    }
+}
+
+/// Formats the remaining time of a cooldown, like `1h 5min` or `30s`
+pub fn format_wait(wait: Duration) -> String {
+    // Rounds up so it never says 0s
+    let seconds = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+
+    match (hours, minutes) {
+        (0, 0) => format!("{seconds}s"),
+        (0, _) if seconds > 0 => format!("{minutes}min {seconds}s"),
+        (0, _) => format!("{minutes}min"),
+        (_, 0) => format!("{hours}h"),
+        _ => format!("{hours}h {minutes}min"),
+    }
+}
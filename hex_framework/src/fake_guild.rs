@@ -0,0 +1,493 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Mutex,
+};
+
+use anyhow::Context;
+use async_trait::async_trait;
+use hex_discord::twilight_model::{
+    channel::{permission_overwrite::PermissionOverwrite, Channel, ChannelType},
+    guild::{Member, Permissions, Role},
+    id::{
+        marker::{ChannelMarker, GuildMarker, RoleMarker, UserMarker},
+        Id,
+    },
+    util::Timestamp,
+};
+use serde_json::json;
+
+use crate::{ChannelEdit, GuildOperations, NewChannel, NewRole, RoleEdit};
+
+#[derive(Debug, Clone, Default)]
+pub struct FakeGuildState {
+    pub channels: Vec<Channel>,
+    pub members: Vec<Member>,
+    pub roles: Vec<Role>,
+    /// Kicked users and the kick reason
+    pub kicks: Vec<(Id<UserMarker>, String)>,
+    /// Banned users and the ban reason
+    pub bans: Vec<(Id<UserMarker>, String)>,
+    /// Unbanned users and the unban reason
+    pub unbans: Vec<(Id<UserMarker>, String)>,
+}
+
+/// An in-memory guild, used to run the AI pipeline end to end without Discord
+#[derive(Debug)]
+pub struct FakeGuild {
+    pub guild_id: Id<GuildMarker>,
+    state: Mutex<FakeGuildState>,
+    next_id: AtomicU64,
+}
+
+impl FakeGuild {
+    pub fn new(guild_id: Id<GuildMarker>) -> Self {
+        Self {
+            guild_id,
+            state: Mutex::new(FakeGuildState::default()),
+            next_id: AtomicU64::new(1000),
+        }
+    }
+
+    pub fn add_category(self, name: impl ToString) -> Self {
+        let category = self.new_channel(NewChannel {
+            name: name.to_string(),
+            kind: ChannelType::GuildCategory,
+            topic: None,
+            parent_id: None,
+            bitrate: None,
+            user_limit: None,
+            rate_limit_per_user: None,
+            tags: vec![],
+        });
+
+        self.state.lock().unwrap().channels.push(category);
+        self
+    }
+
+    /// Adds a text channel, inside the category with the given name if it exists
+    pub fn add_channel(
+        self,
+        name: impl ToString,
+        topic: impl ToString,
+        category: Option<&str>,
+    ) -> Self {
+        let parent_id = category.and_then(|category| {
+            self.state
+                .lock()
+                .unwrap()
+                .channels
+                .iter()
+                .find(|c| {
+                    c.kind == ChannelType::GuildCategory && c.name.as_deref() == Some(category)
+                })
+                .map(|c| c.id)
+        });
+
+        let channel = self.new_channel(NewChannel {
+            name: name.to_string(),
+            kind: ChannelType::GuildText,
+            topic: Some(topic.to_string()),
+            parent_id,
+            bitrate: None,
+            user_limit: None,
+            rate_limit_per_user: None,
+            tags: vec![],
+        });
+
+        self.state.lock().unwrap().channels.push(channel);
+        self
+    }
+
+    pub fn add_member(self, user_id: u64, name: impl ToString) -> Self {
+        let member = serde_json::from_value(json!({
+            "user": {
+                "id": user_id.to_string(),
+                "username": name.to_string(),
+                "discriminator": "0",
+                "avatar": null,
+            },
+            "roles": [],
+            "joined_at": "2024-01-01T00:00:00.000000+00:00",
+            "deaf": false,
+            "mute": false,
+            "flags": 0,
+        }))
+        .expect("The fake member should be valid");
+
+        self.state.lock().unwrap().members.push(member);
+        self
+    }
+
+    pub fn add_role(self, name: impl ToString) -> Self {
+        let role = self.new_role(NewRole {
+            name: name.to_string(),
+            color: None,
+            permissions: Permissions::empty(),
+            hoist: false,
+            mentionable: false,
+        });
+
+        self.state.lock().unwrap().roles.push(role);
+        self
+    }
+
+    /// A snapshot of the guild, to check what the pipeline did
+    pub fn state(&self) -> FakeGuildState {
+        self.state.lock().unwrap().clone()
+    }
+
+    fn new_channel(&self, channel: NewChannel) -> Channel {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let tags = channel
+            .tags
+            .iter()
+            .map(|name| {
+                json!({
+                    "id": self.next_id.fetch_add(1, Ordering::Relaxed).to_string(),
+                    "name": name,
+                    "moderated": false,
+                    "emoji_id": null,
+                    "emoji_name": null,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        serde_json::from_value(json!({
+            "id": id.to_string(),
+            "type": u8::from(channel.kind),
+            "guild_id": self.guild_id,
+            "name": channel.name,
+            "topic": channel.topic,
+            "parent_id": channel.parent_id,
+            "position": id,
+            "permission_overwrites": [],
+            "bitrate": channel.bitrate,
+            "user_limit": channel.user_limit,
+            "rate_limit_per_user": channel.rate_limit_per_user,
+            "available_tags": (!tags.is_empty()).then_some(tags),
+        }))
+        .expect("The fake channel should be valid")
+    }
+
+    fn new_role(&self, role: NewRole) -> Role {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+
+        serde_json::from_value(json!({
+            "id": id.to_string(),
+            "name": role.name,
+            "color": role.color.unwrap_or_default(),
+            "hoist": role.hoist,
+            "mentionable": role.mentionable,
+            "managed": false,
+            "permissions": role.permissions.bits().to_string(),
+            "position": id,
+            "flags": 0,
+        }))
+        .expect("The fake role should be valid")
+    }
+
+    fn check_guild(&self, guild_id: Id<GuildMarker>) -> anyhow::Result<()> {
+        if guild_id != self.guild_id {
+            anyhow::bail!("Unknown guild {guild_id}");
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl GuildOperations for FakeGuild {
+    async fn channels(&self, guild_id: Id<GuildMarker>) -> anyhow::Result<Vec<Channel>> {
+        self.check_guild(guild_id)?;
+        Ok(self.state.lock().unwrap().channels.clone())
+    }
+
+    async fn create_channel(
+        &self,
+        guild_id: Id<GuildMarker>,
+        channel: NewChannel,
+    ) -> anyhow::Result<Channel> {
+        self.check_guild(guild_id)?;
+        let channel = self.new_channel(channel);
+
+        self.state.lock().unwrap().channels.push(channel.clone());
+        Ok(channel)
+    }
+
+    async fn edit_channel(
+        &self,
+        channel_id: Id<ChannelMarker>,
+        edit: ChannelEdit,
+    ) -> anyhow::Result<Channel> {
+        let mut state = self.state.lock().unwrap();
+        let channel = state
+            .channels
+            .iter_mut()
+            .find(|c| c.id == channel_id)
+            .context("Unknown channel")?;
+
+        channel.name = Some(edit.name);
+        if let Some(topic) = edit.topic {
+            channel.topic = Some(topic);
+        }
+        channel.parent_id = edit.parent_id;
+
+        Ok(channel.clone())
+    }
+
+    async fn delete_channel(&self, channel_id: Id<ChannelMarker>) -> anyhow::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let len = state.channels.len();
+        state.channels.retain(|c| c.id != channel_id);
+
+        if state.channels.len() == len {
+            anyhow::bail!("Unknown channel");
+        }
+
+        Ok(())
+    }
+
+    async fn set_channel_positions(
+        &self,
+        guild_id: Id<GuildMarker>,
+        positions: Vec<(Id<ChannelMarker>, u64)>,
+    ) -> anyhow::Result<()> {
+        self.check_guild(guild_id)?;
+
+        let mut state = self.state.lock().unwrap();
+        for (channel_id, position) in positions {
+            let channel = state
+                .channels
+                .iter_mut()
+                .find(|c| c.id == channel_id)
+                .context("Unknown channel")?;
+
+            channel.position = Some(position as i32);
+        }
+
+        Ok(())
+    }
+
+    async fn member(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+    ) -> anyhow::Result<Member> {
+        self.check_guild(guild_id)?;
+        self.state
+            .lock()
+            .unwrap()
+            .members
+            .iter()
+            .find(|m| m.user.id == user_id)
+            .cloned()
+            .context("Unknown member")
+    }
+
+    async fn members(&self, guild_id: Id<GuildMarker>) -> anyhow::Result<Vec<Member>> {
+        self.check_guild(guild_id)?;
+        Ok(self.state.lock().unwrap().members.clone())
+    }
+
+    async fn kick_member(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+        reason: &str,
+    ) -> anyhow::Result<()> {
+        let member = self.member(guild_id, user_id).await?;
+
+        let mut state = self.state.lock().unwrap();
+        state.members.retain(|m| m.user.id != member.user.id);
+        state.kicks.push((user_id, reason.to_string()));
+
+        Ok(())
+    }
+
+    async fn ban_member(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+        reason: &str,
+    ) -> anyhow::Result<()> {
+        self.check_guild(guild_id)?;
+
+        let mut state = self.state.lock().unwrap();
+        state.members.retain(|m| m.user.id != user_id);
+        state.bans.push((user_id, reason.to_string()));
+
+        Ok(())
+    }
+
+    async fn unban_member(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+        reason: &str,
+    ) -> anyhow::Result<()> {
+        self.check_guild(guild_id)?;
+
+        let mut state = self.state.lock().unwrap();
+        if !state.bans.iter().any(|(id, _)| *id == user_id) {
+            anyhow::bail!("Unknown ban");
+        }
+
+        state.bans.retain(|(id, _)| *id != user_id);
+        state.unbans.push((user_id, reason.to_string()));
+
+        Ok(())
+    }
+
+    async fn timeout_member(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+        until: Option<Timestamp>,
+        _reason: &str,
+    ) -> anyhow::Result<()> {
+        self.check_guild(guild_id)?;
+
+        let mut state = self.state.lock().unwrap();
+        let member = state
+            .members
+            .iter_mut()
+            .find(|m| m.user.id == user_id)
+            .context("Unknown member")?;
+
+        member.communication_disabled_until = until;
+        Ok(())
+    }
+
+    async fn roles(&self, guild_id: Id<GuildMarker>) -> anyhow::Result<Vec<Role>> {
+        self.check_guild(guild_id)?;
+        Ok(self.state.lock().unwrap().roles.clone())
+    }
+
+    async fn create_role(&self, guild_id: Id<GuildMarker>, role: NewRole) -> anyhow::Result<Role> {
+        self.check_guild(guild_id)?;
+        let role = self.new_role(role);
+
+        self.state.lock().unwrap().roles.push(role.clone());
+        Ok(role)
+    }
+
+    async fn edit_role(
+        &self,
+        guild_id: Id<GuildMarker>,
+        role_id: Id<RoleMarker>,
+        edit: RoleEdit,
+    ) -> anyhow::Result<Role> {
+        self.check_guild(guild_id)?;
+
+        let mut state = self.state.lock().unwrap();
+        let role = state
+            .roles
+            .iter_mut()
+            .find(|r| r.id == role_id)
+            .context("Unknown role")?;
+
+        if let Some(name) = edit.name {
+            role.name = name;
+        }
+        if let Some(color) = edit.color {
+            role.color = color;
+        }
+        if let Some(permissions) = edit.permissions {
+            role.permissions = permissions;
+        }
+        if let Some(hoist) = edit.hoist {
+            role.hoist = hoist;
+        }
+        if let Some(mentionable) = edit.mentionable {
+            role.mentionable = mentionable;
+        }
+
+        Ok(role.clone())
+    }
+
+    async fn delete_role(
+        &self,
+        guild_id: Id<GuildMarker>,
+        role_id: Id<RoleMarker>,
+    ) -> anyhow::Result<()> {
+        self.check_guild(guild_id)?;
+
+        let mut state = self.state.lock().unwrap();
+        let len = state.roles.len();
+        state.roles.retain(|r| r.id != role_id);
+
+        if state.roles.len() == len {
+            anyhow::bail!("Unknown role");
+        }
+
+        for member in &mut state.members {
+            member.roles.retain(|r| *r != role_id);
+        }
+
+        Ok(())
+    }
+
+    async fn add_member_role(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+        role_id: Id<RoleMarker>,
+    ) -> anyhow::Result<()> {
+        self.check_guild(guild_id)?;
+
+        let mut state = self.state.lock().unwrap();
+        if !state.roles.iter().any(|r| r.id == role_id) {
+            anyhow::bail!("Unknown role");
+        }
+
+        let member = state
+            .members
+            .iter_mut()
+            .find(|m| m.user.id == user_id)
+            .context("Unknown member")?;
+
+        if !member.roles.contains(&role_id) {
+            member.roles.push(role_id);
+        }
+
+        Ok(())
+    }
+
+    async fn remove_member_role(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+        role_id: Id<RoleMarker>,
+    ) -> anyhow::Result<()> {
+        self.check_guild(guild_id)?;
+
+        let mut state = self.state.lock().unwrap();
+        let member = state
+            .members
+            .iter_mut()
+            .find(|m| m.user.id == user_id)
+            .context("Unknown member")?;
+
+        member.roles.retain(|r| *r != role_id);
+        Ok(())
+    }
+
+    async fn set_permission_overwrite(
+        &self,
+        channel_id: Id<ChannelMarker>,
+        overwrite: PermissionOverwrite,
+    ) -> anyhow::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let channel = state
+            .channels
+            .iter_mut()
+            .find(|c| c.id == channel_id)
+            .context("Unknown channel")?;
+
+        let overwrites = channel.permission_overwrites.get_or_insert_with(Vec::new);
+        overwrites.retain(|o| o.id != overwrite.id || o.kind != overwrite.kind);
+        overwrites.push(overwrite);
+
+        Ok(())
+    }
+}
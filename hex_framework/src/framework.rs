@@ -4,5 +4,5 @@ use crate::{Command, HexClient};
 
 pub struct Framework {
     pub client: Arc<HexClient>,
-    pub commands: HashMap<String, Box<(dyn Command + Send + Sync)>>,
+    pub commands: HashMap<String, Box<dyn Command + Send + Sync>>,
 }
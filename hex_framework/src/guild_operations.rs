@@ -0,0 +1,459 @@
+use std::fmt::Debug;
+
+use async_trait::async_trait;
+use hex_discord::{
+    twilight_http::{
+        request::{
+            guild::update_guild_channel_positions::Position, AuditLogReason, Request,
+            TryIntoRequest,
+        },
+        routing::Route,
+    },
+    twilight_model::{
+        channel::{
+            permission_overwrite::{PermissionOverwrite, PermissionOverwriteType},
+            Channel, ChannelType,
+        },
+        guild::{Member, Permissions, Role},
+        http::permission_overwrite::{
+            PermissionOverwrite as HttpPermissionOverwrite,
+            PermissionOverwriteType as HttpPermissionOverwriteType,
+        },
+        id::{
+            marker::{ChannelMarker, GuildMarker, RoleMarker, UserMarker},
+            Id,
+        },
+        util::Timestamp,
+    },
+};
+
+use crate::HexClient;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewChannel {
+    pub name: String,
+    pub kind: ChannelType,
+    pub topic: Option<String>,
+    pub parent_id: Option<Id<ChannelMarker>>,
+    /// Voice and stage channels only
+    pub bitrate: Option<u32>,
+    /// Voice and stage channels only
+    pub user_limit: Option<u16>,
+    /// Slowmode, in seconds
+    pub rate_limit_per_user: Option<u16>,
+    /// Names of the forum tags. Forum channels only
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelEdit {
+    pub name: String,
+    /// Text, announcement and forum channels only. `None` leaves it unchanged
+    pub topic: Option<String>,
+    /// `None` removes the channel from its category
+    pub parent_id: Option<Id<ChannelMarker>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewRole {
+    pub name: String,
+    pub color: Option<u32>,
+    pub permissions: Permissions,
+    pub hoist: bool,
+    pub mentionable: bool,
+}
+
+/// Fields set to `None` are left unchanged
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RoleEdit {
+    pub name: Option<String>,
+    pub color: Option<u32>,
+    pub permissions: Option<Permissions>,
+    pub hoist: Option<bool>,
+    pub mentionable: Option<bool>,
+}
+
+/// Guild operations used by the AI pipeline. Implemented by `HexClient` over the Discord API
+/// and by `FakeGuild` in memory, so the pipeline can run without Discord.
+#[async_trait]
+pub trait GuildOperations: Debug + Send + Sync {
+    async fn channels(&self, guild_id: Id<GuildMarker>) -> anyhow::Result<Vec<Channel>>;
+
+    async fn create_channel(
+        &self,
+        guild_id: Id<GuildMarker>,
+        channel: NewChannel,
+    ) -> anyhow::Result<Channel>;
+
+    async fn edit_channel(
+        &self,
+        channel_id: Id<ChannelMarker>,
+        edit: ChannelEdit,
+    ) -> anyhow::Result<Channel>;
+
+    async fn delete_channel(&self, channel_id: Id<ChannelMarker>) -> anyhow::Result<()>;
+
+    /// Sets the position of each channel. Channels that are not listed keep their position
+    async fn set_channel_positions(
+        &self,
+        guild_id: Id<GuildMarker>,
+        positions: Vec<(Id<ChannelMarker>, u64)>,
+    ) -> anyhow::Result<()>;
+
+    async fn member(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+    ) -> anyhow::Result<Member>;
+
+    async fn members(&self, guild_id: Id<GuildMarker>) -> anyhow::Result<Vec<Member>>;
+
+    async fn kick_member(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+        reason: &str,
+    ) -> anyhow::Result<()>;
+
+    async fn ban_member(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+        reason: &str,
+    ) -> anyhow::Result<()>;
+
+    async fn unban_member(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+        reason: &str,
+    ) -> anyhow::Result<()>;
+
+    /// Disables the communication of the member until the given time. `None` removes the timeout
+    async fn timeout_member(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+        until: Option<Timestamp>,
+        reason: &str,
+    ) -> anyhow::Result<()>;
+
+    async fn roles(&self, guild_id: Id<GuildMarker>) -> anyhow::Result<Vec<Role>>;
+
+    async fn create_role(&self, guild_id: Id<GuildMarker>, role: NewRole) -> anyhow::Result<Role>;
+
+    async fn edit_role(
+        &self,
+        guild_id: Id<GuildMarker>,
+        role_id: Id<RoleMarker>,
+        edit: RoleEdit,
+    ) -> anyhow::Result<Role>;
+
+    async fn delete_role(
+        &self,
+        guild_id: Id<GuildMarker>,
+        role_id: Id<RoleMarker>,
+    ) -> anyhow::Result<()>;
+
+    async fn add_member_role(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+        role_id: Id<RoleMarker>,
+    ) -> anyhow::Result<()>;
+
+    async fn remove_member_role(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+        role_id: Id<RoleMarker>,
+    ) -> anyhow::Result<()>;
+
+    /// Creates or replaces the overwrite for the role or member in the channel
+    async fn set_permission_overwrite(
+        &self,
+        channel_id: Id<ChannelMarker>,
+        overwrite: PermissionOverwrite,
+    ) -> anyhow::Result<()>;
+}
+
+#[async_trait]
+impl GuildOperations for HexClient {
+    async fn channels(&self, guild_id: Id<GuildMarker>) -> anyhow::Result<Vec<Channel>> {
+        Ok(self.http.guild_channels(guild_id).await?.models().await?)
+    }
+
+    async fn create_channel(
+        &self,
+        guild_id: Id<GuildMarker>,
+        channel: NewChannel,
+    ) -> anyhow::Result<Channel> {
+        let mut request = self
+            .http
+            .create_guild_channel(guild_id, &channel.name)?
+            .kind(channel.kind);
+
+        if let Some(topic) = &channel.topic {
+            request = request.topic(topic)?;
+        }
+
+        if let Some(parent_id) = channel.parent_id {
+            request = request.parent_id(parent_id);
+        }
+
+        if let Some(bitrate) = channel.bitrate {
+            request = request.bitrate(bitrate)?;
+        }
+
+        if let Some(user_limit) = channel.user_limit {
+            request = request.user_limit(user_limit);
+        }
+
+        if let Some(rate_limit_per_user) = channel.rate_limit_per_user {
+            request = request.rate_limit_per_user(rate_limit_per_user)?;
+        }
+
+        if channel.tags.is_empty() {
+            return Ok(request.await?.model().await?);
+        }
+
+        // twilight's `ForumTag` needs an ID, but Discord assigns them when the
+        // channel is created, so the tags are added to the JSON body without one
+        let request = request.try_into_request()?;
+        let mut body: serde_json::Value = serde_json::from_slice(request.body().unwrap_or(b"{}"))?;
+        body["available_tags"] = channel
+            .tags
+            .iter()
+            .map(|name| serde_json::json!({ "name": name }))
+            .collect();
+
+        let request = Request::builder(&Route::CreateChannel {
+            guild_id: guild_id.get(),
+        })
+        .json(&body)?
+        .build();
+
+        Ok(self.http.request::<Channel>(request).await?.model().await?)
+    }
+
+    async fn edit_channel(
+        &self,
+        channel_id: Id<ChannelMarker>,
+        edit: ChannelEdit,
+    ) -> anyhow::Result<Channel> {
+        let mut request = self
+            .http
+            .update_channel(channel_id)
+            .name(&edit.name)?
+            .parent_id(edit.parent_id);
+
+        if let Some(topic) = &edit.topic {
+            request = request.topic(topic)?;
+        }
+
+        Ok(request.await?.model().await?)
+    }
+
+    async fn delete_channel(&self, channel_id: Id<ChannelMarker>) -> anyhow::Result<()> {
+        self.http.delete_channel(channel_id).await?;
+        Ok(())
+    }
+
+    async fn set_channel_positions(
+        &self,
+        guild_id: Id<GuildMarker>,
+        positions: Vec<(Id<ChannelMarker>, u64)>,
+    ) -> anyhow::Result<()> {
+        let positions = positions
+            .into_iter()
+            .map(Position::from)
+            .collect::<Vec<_>>();
+
+        self.http
+            .update_guild_channel_positions(guild_id, &positions)
+            .await?;
+        Ok(())
+    }
+
+    async fn member(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+    ) -> anyhow::Result<Member> {
+        Ok(self
+            .http
+            .guild_member(guild_id, user_id)
+            .await?
+            .model()
+            .await?)
+    }
+
+    async fn members(&self, guild_id: Id<GuildMarker>) -> anyhow::Result<Vec<Member>> {
+        Ok(self
+            .http
+            .guild_members(guild_id)
+            .limit(1000)?
+            .await?
+            .models()
+            .await?)
+    }
+
+    async fn kick_member(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+        reason: &str,
+    ) -> anyhow::Result<()> {
+        self.http
+            .remove_guild_member(guild_id, user_id)
+            .reason(reason)?
+            .await?;
+        Ok(())
+    }
+
+    async fn ban_member(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+        reason: &str,
+    ) -> anyhow::Result<()> {
+        self.http
+            .create_ban(guild_id, user_id)
+            .reason(reason)?
+            .await?;
+        Ok(())
+    }
+
+    async fn unban_member(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+        reason: &str,
+    ) -> anyhow::Result<()> {
+        self.http
+            .delete_ban(guild_id, user_id)
+            .reason(reason)?
+            .await?;
+        Ok(())
+    }
+
+    async fn timeout_member(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+        until: Option<Timestamp>,
+        reason: &str,
+    ) -> anyhow::Result<()> {
+        self.http
+            .update_guild_member(guild_id, user_id)
+            .communication_disabled_until(until)?
+            .reason(reason)?
+            .await?;
+        Ok(())
+    }
+
+    async fn roles(&self, guild_id: Id<GuildMarker>) -> anyhow::Result<Vec<Role>> {
+        Ok(self.http.roles(guild_id).await?.models().await?)
+    }
+
+    async fn create_role(&self, guild_id: Id<GuildMarker>, role: NewRole) -> anyhow::Result<Role> {
+        let mut request = self
+            .http
+            .create_role(guild_id)
+            .name(&role.name)
+            .permissions(role.permissions)
+            .hoist(role.hoist)
+            .mentionable(role.mentionable);
+
+        if let Some(color) = role.color {
+            request = request.color(color);
+        }
+
+        Ok(request.await?.model().await?)
+    }
+
+    async fn edit_role(
+        &self,
+        guild_id: Id<GuildMarker>,
+        role_id: Id<RoleMarker>,
+        edit: RoleEdit,
+    ) -> anyhow::Result<Role> {
+        let mut request = self.http.update_role(guild_id, role_id);
+
+        if let Some(name) = &edit.name {
+            request = request.name(Some(name));
+        }
+        if let Some(color) = edit.color {
+            request = request.color(Some(color));
+        }
+        if let Some(permissions) = edit.permissions {
+            request = request.permissions(permissions);
+        }
+        if let Some(hoist) = edit.hoist {
+            request = request.hoist(hoist);
+        }
+        if let Some(mentionable) = edit.mentionable {
+            request = request.mentionable(mentionable);
+        }
+
+        Ok(request.await?.model().await?)
+    }
+
+    async fn delete_role(
+        &self,
+        guild_id: Id<GuildMarker>,
+        role_id: Id<RoleMarker>,
+    ) -> anyhow::Result<()> {
+        self.http.delete_role(guild_id, role_id).await?;
+        Ok(())
+    }
+
+    async fn add_member_role(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+        role_id: Id<RoleMarker>,
+    ) -> anyhow::Result<()> {
+        self.http
+            .add_guild_member_role(guild_id, user_id, role_id)
+            .await?;
+        Ok(())
+    }
+
+    async fn remove_member_role(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+        role_id: Id<RoleMarker>,
+    ) -> anyhow::Result<()> {
+        self.http
+            .remove_guild_member_role(guild_id, user_id, role_id)
+            .await?;
+        Ok(())
+    }
+
+    async fn set_permission_overwrite(
+        &self,
+        channel_id: Id<ChannelMarker>,
+        overwrite: PermissionOverwrite,
+    ) -> anyhow::Result<()> {
+        let kind = match overwrite.kind {
+            PermissionOverwriteType::Member => HttpPermissionOverwriteType::Member,
+            PermissionOverwriteType::Role => HttpPermissionOverwriteType::Role,
+            kind => anyhow::bail!("Unsupported permission overwrite type: {kind:?}"),
+        };
+
+        self.http
+            .update_channel_permission(
+                channel_id,
+                &HttpPermissionOverwrite {
+                    allow: Some(overwrite.allow),
+                    deny: Some(overwrite.deny),
+                    id: overwrite.id,
+                    kind,
+                },
+            )
+            .await?;
+        Ok(())
+    }
+}
@@ -11,6 +11,8 @@ use std::sync::Arc;
 pub struct HexClient {
     pub http: Arc<DiscordHttpClient>,
     pub user_id: Id<UserMarker>,
+    /// Owner of the bot application, or the members of its team
+    pub owner_ids: Vec<Id<UserMarker>>,
 }
 
 impl HexClient {
@@ -18,10 +20,25 @@ impl HexClient {
         let http = Arc::new(DiscordHttpClient::new(token));
 
         let user = http.current_user().await?.model().await?;
+        let application = http.current_user_application().await?.model().await?;
+
+        let owner_ids = match application.team {
+            Some(team) => team
+                .members
+                .into_iter()
+                .map(|member| member.user.id)
+                .collect(),
+            None => application
+                .owner
+                .into_iter()
+                .map(|owner| owner.id)
+                .collect(),
+        };
 
         Ok(Self {
             http,
             user_id: user.id,
+            owner_ids,
         })
     }
 
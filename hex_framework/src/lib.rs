@@ -2,8 +2,11 @@ mod command;
 mod command_builder;
 mod command_context;
 mod context_helper;
+mod cooldown;
 mod embed_pagination;
+mod fake_guild;
 mod framework;
+mod guild_operations;
 mod hex_client;
 mod option_handler;
 mod response;
@@ -14,7 +17,12 @@ pub mod watcher;
 pub use command::*;
 pub use command_builder::*;
 pub use command_context::CommandContext;
+pub use context_helper::create_channel_confirmation;
+pub use cooldown::{format_wait, Cooldown, CooldownBucket, Cooldowns};
 pub use embed_pagination::EmbedPagination;
+pub use fake_guild::{FakeGuild, FakeGuildState};
 pub use framework::Framework;
+pub use guild_operations::{ChannelEdit, GuildOperations, NewChannel, NewRole, RoleEdit};
 pub use hex_client::HexClient;
+pub use option_handler::Mentionable;
 pub use response::Response;
@@ -1,20 +1,89 @@
-use hex_discord::twilight_model::{
-    application::interaction::application_command::CommandOptionValue, user::User,
+use anyhow::Context;
+use hex_discord::{
+    twilight_model::{
+        application::interaction::application_command::{
+            CommandDataOption, CommandInteractionDataResolved, CommandOptionValue,
+            InteractionChannel,
+        },
+        channel::Attachment,
+        guild::Role,
+        user::User,
+    },
+    InteractionData,
 };
 
 use crate::CommandContext;
 
+/// The value of a mentionable option
+#[derive(Debug, Clone, PartialEq)]
+pub enum Mentionable {
+    User(User),
+    Role(Role),
+}
+
 pub struct OptionHandler<'a> {
     pub ctx: &'a CommandContext,
 }
 
 impl<'a> OptionHandler<'a> {
+    /// Name of the invoked subcommand group, if the subcommand is inside one
+    pub fn subcommand_group(&self) -> Option<String> {
+        self.ctx
+            .options
+            .first()
+            .filter(|o| matches!(o.value, CommandOptionValue::SubCommandGroup(_)))
+            .map(|o| o.name.clone())
+    }
+
+    /// Name of the invoked subcommand, looking inside its group if there's one
+    pub fn subcommand(&self) -> Option<String> {
+        let option = match self.ctx.options.first().map(|o| &o.value) {
+            Some(CommandOptionValue::SubCommandGroup(options)) => options.first(),
+            _ => self.ctx.options.first(),
+        };
+
+        option
+            .filter(|o| matches!(o.value, CommandOptionValue::SubCommand(_)))
+            .map(|o| o.name.clone())
+    }
+
+    /// The options of the invoked subcommand, or of the command itself when it has none
+    pub fn leaf_options(&self) -> &[CommandDataOption] {
+        let mut options = self.ctx.options.as_slice();
+        while let Some(
+            CommandOptionValue::SubCommandGroup(nested) | CommandOptionValue::SubCommand(nested),
+        ) = options.first().map(|o| &o.value)
+        {
+            options = nested;
+        }
+
+        options
+    }
+
+    /// The option the user is typing in an autocomplete interaction, with what was typed so far
+    pub fn focused(&self) -> Option<(String, String)> {
+        self.leaf_options()
+            .iter()
+            .find_map(|option| match &option.value {
+                CommandOptionValue::Focused(value, _) => Some((option.name.clone(), value.clone())),
+                _ => None,
+            })
+    }
+
+    /// Users, channels, roles and attachments Discord sent along with the options
+    fn resolved(&self) -> Option<&CommandInteractionDataResolved> {
+        match &self.ctx.interaction.data {
+            Some(InteractionData::ApplicationCommand(data)) => data.resolved.as_ref(),
+            _ => None,
+        }
+    }
+
     pub fn get_option_value(
         &self,
         option_name: impl Into<String>,
     ) -> anyhow::Result<Option<CommandOptionValue>> {
         let option_name: String = option_name.into();
-        let Some(option) = self.ctx.options.iter().find(|o| o.name == option_name) else {
+        let Some(option) = self.leaf_options().iter().find(|o| o.name == option_name) else {
             return Ok(None);
         };
 
@@ -61,14 +130,96 @@ impl<'a> OptionHandler<'a> {
 
         Ok(match value {
             CommandOptionValue::User(user_id) => {
-                let user = self.ctx.client.get_user(user_id).await?;
+                let resolved = self
+                    .resolved()
+                    .and_then(|resolved| resolved.users.get(&user_id).cloned());
 
-                Some(user)
+                match resolved {
+                    Some(user) => Some(user),
+                    None => Some(self.ctx.client.get_user(user_id).await?),
+                }
             }
             _ => None,
         })
     }
 
+    pub fn get_channel(
+        &self,
+        option_name: impl Into<String>,
+    ) -> anyhow::Result<Option<InteractionChannel>> {
+        let Some(value) = self.get_option_value(option_name)? else {
+            return Ok(None);
+        };
+
+        Ok(match value {
+            CommandOptionValue::Channel(channel_id) => Some(
+                self.resolved()
+                    .and_then(|resolved| resolved.channels.get(&channel_id).cloned())
+                    .context("The channel was not resolved")?,
+            ),
+            _ => None,
+        })
+    }
+
+    pub fn get_role(&self, option_name: impl Into<String>) -> anyhow::Result<Option<Role>> {
+        let Some(value) = self.get_option_value(option_name)? else {
+            return Ok(None);
+        };
+
+        Ok(match value {
+            CommandOptionValue::Role(role_id) => Some(
+                self.resolved()
+                    .and_then(|resolved| resolved.roles.get(&role_id).cloned())
+                    .context("The role was not resolved")?,
+            ),
+            _ => None,
+        })
+    }
+
+    pub fn get_attachment(
+        &self,
+        option_name: impl Into<String>,
+    ) -> anyhow::Result<Option<Attachment>> {
+        let Some(value) = self.get_option_value(option_name)? else {
+            return Ok(None);
+        };
+
+        Ok(match value {
+            CommandOptionValue::Attachment(attachment_id) => Some(
+                self.resolved()
+                    .and_then(|resolved| resolved.attachments.get(&attachment_id).cloned())
+                    .context("The attachment was not resolved")?,
+            ),
+            _ => None,
+        })
+    }
+
+    pub fn get_mentionable(
+        &self,
+        option_name: impl Into<String>,
+    ) -> anyhow::Result<Option<Mentionable>> {
+        let Some(value) = self.get_option_value(option_name)? else {
+            return Ok(None);
+        };
+
+        let CommandOptionValue::Mentionable(id) = value else {
+            return Ok(None);
+        };
+
+        let resolved = self
+            .resolved()
+            .context("The mentionable was not resolved")?;
+        if let Some(user) = resolved.users.get(&id.cast()) {
+            return Ok(Some(Mentionable::User(user.clone())));
+        }
+
+        let role = resolved
+            .roles
+            .get(&id.cast())
+            .context("The mentionable was not resolved")?;
+        Ok(Some(Mentionable::Role(role.clone())))
+    }
+
     pub fn get_boolean(&self, option_name: impl Into<String>) -> anyhow::Result<Option<bool>> {
         let Some(value) = self.get_option_value(option_name)? else {
             return Ok(None);
@@ -14,6 +14,9 @@ pub struct Response {
     pub flags: Option<MessageFlags>,
     pub components: Option<Vec<Component>>,
     pub attachments: Option<Vec<Attachment>>,
+    /// Action rows as JSON, for components twilight can't represent. Only the requests
+    /// that send JSON, like `update_interaction_reply`, include them
+    pub raw_components: Option<Vec<serde_json::Value>>,
 
     pub prefixed: bool,
 }
@@ -55,6 +58,7 @@ impl Response {
     pub fn remove_all_components(self) -> Response {
         Response {
             components: Some(vec![]),
+            raw_components: None,
             ..self
         }
     }
@@ -90,6 +94,12 @@ impl Response {
         self
     }
 
+    /// Adds an action row built as JSON, e.g. by `AutoSelectMenuBuilder`
+    pub fn add_raw_component(mut self, row: serde_json::Value) -> Response {
+        self.raw_components.get_or_insert_with(Vec::new).push(row);
+        self
+    }
+
     pub fn add_embed(self, embed: EmbedBuilder) -> Response {
         let mut embeds = self.embeds.unwrap_or_default();
         embeds.push(embed);
@@ -154,8 +164,23 @@ impl Response {
         self.add_emoji_prefix("✅")
     }
 
-    pub fn to_json(self) -> Vec<u8> {
+    pub fn to_json(mut self) -> Vec<u8> {
+        let raw_components = self.raw_components.take();
         let data = InteractionResponseData::from(self);
+
+        let Some(raw_components) = raw_components else {
+            return serde_json::to_vec(&data).unwrap_or_default();
+        };
+
+        // The raw rows come after the ones twilight knows
+        let mut data = serde_json::to_value(&data).unwrap_or_default();
+        let mut components = match data.get_mut("components").map(serde_json::Value::take) {
+            Some(serde_json::Value::Array(components)) => components,
+            _ => vec![],
+        };
+        components.extend(raw_components);
+        data["components"] = serde_json::Value::Array(components);
+
         serde_json::to_vec(&data).unwrap_or_default()
     }
 }
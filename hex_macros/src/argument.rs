@@ -1,7 +1,7 @@
 use syn::{spanned::Spanned, Attribute, Error, FnArg, Type};
 
 use crate::{
-    attr::{self, Attr},
+    attr::{self, Attr, Literal, Value},
     common::Result,
     util,
 };
@@ -13,17 +13,38 @@ pub struct Argument {
     pub ty: Type,
     pub description: String,
     pub min_max_length: Option<(u16, u16)>,
+    /// Function suggesting values for this argument while the user types
+    pub autocomplete: Option<syn::Ident>,
+    /// Static choices, as name and value
+    pub choices: Vec<(String, Literal)>,
+    pub min_value: Option<Literal>,
+    pub max_value: Option<Literal>,
+    pub channel_types: Vec<syn::Ident>,
 }
 
+/// Attributes parsed from their raw tokens, since `parse_meta` rejects negative numbers
+const RAW_ATTRIBUTES: &[&str] = &[
+    "choice",
+    "choices",
+    "min_value",
+    "max_value",
+    "channel_types",
+];
+
 impl Argument {
     pub fn new(arg: FnArg) -> Result<Self> {
         let pat = util::get_pat(&arg)?;
         let ident = util::get_ident(&pat.pat)?;
         let ty = *pat.ty.clone();
 
-        let rename = pat
+        let (raw_attrs, meta_attrs): (Vec<_>, Vec<_>) = pat
             .attrs
             .iter()
+            .partition(|attr| RAW_ATTRIBUTES.iter().any(|name| attr.path.is_ident(name)));
+
+        let rename = meta_attrs
+            .iter()
+            .copied()
             .map(extract_attribute_renamed)
             .collect::<Result<Vec<_>>>()
             .into_iter()
@@ -33,9 +54,9 @@ impl Argument {
 
         let name = rename.first().cloned().unwrap_or(ident.to_string());
 
-        let mut descriptions = pat
-            .attrs
+        let mut descriptions = meta_attrs
             .iter()
+            .copied()
             .map(extract_attribute_description)
             .collect::<Result<Vec<_>>>()
             .into_iter()
@@ -43,9 +64,9 @@ impl Argument {
             .flatten()
             .collect::<Vec<_>>();
 
-        let min_max_length = pat
-            .attrs
+        let min_max_length = meta_attrs
             .iter()
+            .copied()
             .map(extract_attribute_min_max_length)
             .collect::<Vec<_>>()
             .into_iter()
@@ -53,6 +74,56 @@ impl Argument {
             .flatten()
             .collect::<Vec<_>>();
 
+        let autocomplete = meta_attrs
+            .iter()
+            .copied()
+            .map(extract_attribute_autocomplete)
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+
+        let mut choices = Vec::new();
+        let mut min_value = None;
+        let mut max_value = None;
+        let mut channel_types = Vec::new();
+        for attr in raw_attrs {
+            if attr.path.is_ident("choices") {
+                choices.extend(
+                    attr::parse_literals(attr)?
+                        .into_iter()
+                        .map(|value| (value.to_string(), value)),
+                );
+            } else if attr.path.is_ident("choice") {
+                match attr::parse_literals(attr)?.as_slice() {
+                    [Literal::Str(name), value] => choices.push((name.clone(), value.clone())),
+                    _ => {
+                        return Err(Error::new(
+                            attr.span(),
+                            "Expected a name and a value, like #[choice(\"Name\", 1)]",
+                        ))
+                    }
+                }
+            } else if attr.path.is_ident("channel_types") {
+                channel_types.extend(attr::parse_idents(attr)?);
+            } else {
+                let value = match attr::parse_literals(attr)?.as_slice() {
+                    [value @ (Literal::Int(_) | Literal::Float(_))] => value.clone(),
+                    _ => return Err(Error::new(attr.span(), "Expected a single number")),
+                };
+
+                if attr.path.is_ident("min_value") {
+                    min_value = Some(value);
+                } else {
+                    max_value = Some(value);
+                }
+            }
+        }
+
+        if choices.len() > 25 {
+            return Err(Error::new(arg.span(), "Discord allows at most 25 choices"));
+        }
+
         if descriptions.len() > 1 {
             // We only want a single description attribute
             return Err(Error::new(
@@ -70,6 +141,11 @@ impl Argument {
             description: descriptions.remove(0),
             ty,
             min_max_length: min_max_length.first().cloned(),
+            autocomplete: autocomplete.first().cloned(),
+            choices,
+            min_value,
+            max_value,
+            channel_types,
         })
     }
 }
@@ -111,3 +187,19 @@ fn extract_attribute_min_max_length(attr: &Attribute) -> Result<Option<(u16, u16
         }
     })
 }
+
+fn extract_attribute_autocomplete(attr: &Attribute) -> Result<Option<syn::Ident>> {
+    exec(attr, |parsed| {
+        if parsed.path.is_ident("autocomplete") {
+            parsed.parse_value(|value| match value {
+                Value::Ident(ident) => Ok(Some(ident.clone())),
+                _ => Err(Error::new(
+                    value.span(),
+                    "Argument must be the name of a function",
+                )),
+            })
+        } else {
+            Ok(None)
+        }
+    })
+}
@@ -1,6 +1,9 @@
 use proc_macro2::TokenStream;
 use quote::ToTokens;
-use syn::{spanned::Spanned, Attribute, Error, Ident, Lit, Meta, NestedMeta, Path};
+use syn::{
+    punctuated::Punctuated, spanned::Spanned, Attribute, Error, Expr, ExprLit, ExprUnary, Ident,
+    Lit, Meta, NestedMeta, Path, Token, UnOp,
+};
 
 use crate::common::Result;
 
@@ -136,3 +139,69 @@ impl ToTokens for Attr {
 pub fn parse_attribute(attribute: &Attribute) -> Result<Attr> {
     Attr::parse_one(attribute)
 }
+
+/// A literal given to an attribute like `#[choices("a", "b")]` or `#[min_value(-5)]`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    Str(String),
+    Int(i64),
+    Float(f64),
+}
+
+impl std::fmt::Display for Literal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Literal::Str(value) => f.write_str(value),
+            Literal::Int(value) => write!(f, "{value}"),
+            Literal::Float(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+/// Parses the comma separated literals of an attribute. Unlike `parse_meta`, this accepts
+/// negative numbers.
+pub fn parse_literals(attribute: &Attribute) -> Result<Vec<Literal>> {
+    attribute
+        .parse_args_with(Punctuated::<Expr, Token![,]>::parse_terminated)?
+        .iter()
+        .map(|expr| parse_literal(expr, false))
+        .collect()
+}
+
+/// Parses the comma separated identifiers of an attribute, like `#[channel_types(GuildText)]`
+pub fn parse_idents(attribute: &Attribute) -> Result<Vec<Ident>> {
+    Ok(attribute
+        .parse_args_with(Punctuated::<Ident, Token![,]>::parse_terminated)?
+        .into_iter()
+        .collect())
+}
+
+fn parse_literal(expr: &Expr, negative: bool) -> Result<Literal> {
+    match expr {
+        Expr::Unary(ExprUnary {
+            op: UnOp::Neg(_),
+            expr,
+            ..
+        }) if !negative => parse_literal(expr, true),
+        Expr::Lit(ExprLit {
+            lit: Lit::Str(lit), ..
+        }) if !negative => Ok(Literal::Str(lit.value())),
+        Expr::Lit(ExprLit {
+            lit: Lit::Int(lit), ..
+        }) => {
+            let value = lit.base10_parse::<i64>()?;
+            Ok(Literal::Int(if negative { -value } else { value }))
+        }
+        Expr::Lit(ExprLit {
+            lit: Lit::Float(lit),
+            ..
+        }) => {
+            let value = lit.base10_parse::<f64>()?;
+            Ok(Literal::Float(if negative { -value } else { value }))
+        }
+        _ => Err(Error::new(
+            expr.span(),
+            "Expected a string or number literal",
+        )),
+    }
+}
@@ -5,6 +5,8 @@ use syn::{parse2, spanned::Spanned, Block, Error, ItemFn, Lit, Signature};
 
 use crate::{
     argument::Argument,
+    attr::Literal,
+    command_config::config_tokens,
     common::Result,
     util::{self, capitalize, is_optional, OptionTypeWrapper},
 };
@@ -40,31 +42,33 @@ pub fn command(attr: TokenStream2, input: TokenStream2) -> Result<TokenStream2>
 
     let args = parse_arguments(&mut sig, &mut block)?;
 
-    let option_tokens = {
-        let mut stream = quote!();
-        for arg in args.iter() {
-            let name = &arg.name.to_string();
-            let description = &arg.description;
+    let option_tokens = option_tokens(&args)?;
+    let config_tokens = config_tokens(&attrs)?;
 
-            let ty: OptionTypeWrapper = arg.ty.clone().into();
-            let required = !is_optional(&arg.ty);
-
-            let mut builder_stream = quote!();
-
-            builder_stream.extend(quote!(
-                CommandOptionBuilder::new(#name, #description, #ty).set_required(#required)
-            ));
-
-            if let Some((min_length, max_length)) = arg.min_max_length {
-                builder_stream.extend(quote!(
-                    .set_min_max_length(#min_length, #max_length)
-                ))
+    let routes = args
+        .iter()
+        .filter_map(|arg| {
+            let name = &arg.name;
+            let fun = arg.autocomplete.as_ref()?;
+            Some(quote!(#name => #fun(&ctx, value).await,))
+        })
+        .collect::<Vec<_>>();
+
+    let autocomplete_tokens = if routes.is_empty() {
+        quote!()
+    } else {
+        quote! {
+            async fn autocomplete(&self, ctx: CommandContext) -> anyhow::Result<Vec<CommandOptionChoice>> {
+                let Some((name, value)) = ctx.options().focused() else {
+                    return Ok(vec![]);
+                };
+
+                match name.as_str() {
+                    #(#routes)*
+                    _ => Ok(vec![]),
+                }
             }
-
-            stream.extend(quote!(.add_option(#builder_stream)))
         }
-
-        stream
     };
 
     let struct_name = format_ident!("{}Command", capitalize(&name));
@@ -75,23 +79,154 @@ pub fn command(attr: TokenStream2, input: TokenStream2) -> Result<TokenStream2>
         #[async_trait]
         impl Command for #struct_name {
             fn command_config(&self) -> CommandConfig {
-                CommandConfig
+                #config_tokens
             }
 
             fn build_command(&self, application_id: Id<ApplicationMarker>) -> CommandBuilder {
                 CommandBuilder::new(application_id, #command_name, #description)
+                    .set_config(&self.command_config())
                     #option_tokens
             }
 
             async fn run(&self, mut ctx: CommandContext) -> anyhow::Result<()> {
                 #block
             }
+
+            #autocomplete_tokens
         }
     };
 
     Ok(expanded)
 }
 
+/// The `.add_option(...)` calls declaring the arguments, for a command or a subcommand builder
+pub fn option_tokens(args: &[Argument]) -> Result<TokenStream2> {
+    let mut stream = quote!();
+    for arg in args.iter() {
+        let name = &arg.name.to_string();
+        let description = &arg.description;
+
+        let ty: OptionTypeWrapper = arg.ty.clone().into();
+        let required = !is_optional(&arg.ty);
+
+        let mut builder_stream = quote!();
+
+        builder_stream.extend(quote!(
+            CommandOptionBuilder::new(#name, #description, #ty).set_required(#required)
+        ));
+
+        if let Some((min_length, max_length)) = arg.min_max_length {
+            if ty.0 != CommandOptionType::String {
+                return Err(Error::new(
+                    arg.ident.span(),
+                    "min_max_length only applies to String arguments",
+                ));
+            }
+
+            builder_stream.extend(quote!(
+                .set_min_max_length(#min_length, #max_length)
+            ))
+        }
+
+        if arg.autocomplete.is_some() {
+            if !arg.choices.is_empty() {
+                return Err(Error::new(
+                    arg.ident.span(),
+                    "Arguments can't have both choices and autocomplete",
+                ));
+            }
+
+            builder_stream.extend(quote!(.set_autocomplete(true)))
+        }
+
+        for (choice_name, value) in arg.choices.iter() {
+            builder_stream.extend(match (ty.0, value) {
+                (CommandOptionType::String, Literal::Str(value)) => {
+                    quote!(.add_string_choice(#choice_name, #value))
+                }
+                (CommandOptionType::Integer, Literal::Int(value)) => {
+                    quote!(.add_integer_choice(#choice_name, #value))
+                }
+                (CommandOptionType::Number, Literal::Int(value)) => {
+                    let value = *value as f64;
+                    quote!(.add_number_choice(#choice_name, #value))
+                }
+                (CommandOptionType::Number, Literal::Float(value)) => {
+                    quote!(.add_number_choice(#choice_name, #value))
+                }
+                _ => {
+                    return Err(Error::new(
+                        arg.ident.span(),
+                        "Choices must match the argument type: String, i64 or f64",
+                    ))
+                }
+            });
+        }
+
+        if arg.min_value.is_some() || arg.max_value.is_some() {
+            builder_stream.extend(match ty.0 {
+                CommandOptionType::Integer => {
+                    let (min, max) = (
+                        integer_tokens(arg, &arg.min_value)?,
+                        integer_tokens(arg, &arg.max_value)?,
+                    );
+                    quote!(.set_min_max_integer(#min, #max))
+                }
+                CommandOptionType::Number => {
+                    let (min, max) = (number_tokens(&arg.min_value), number_tokens(&arg.max_value));
+                    quote!(.set_min_max_number(#min, #max))
+                }
+                _ => {
+                    return Err(Error::new(
+                        arg.ident.span(),
+                        "min_value and max_value only apply to i64 and f64 arguments",
+                    ))
+                }
+            });
+        }
+
+        if !arg.channel_types.is_empty() {
+            if ty.0 != CommandOptionType::Channel {
+                return Err(Error::new(
+                    arg.ident.span(),
+                    "channel_types only applies to channel arguments",
+                ));
+            }
+
+            let channel_types = &arg.channel_types;
+            builder_stream.extend(quote!(
+                .set_channel_types(vec![#(ChannelType::#channel_types),*])
+            ))
+        }
+
+        stream.extend(quote!(.add_option(#builder_stream)))
+    }
+
+    Ok(stream)
+}
+
+fn integer_tokens(arg: &Argument, value: &Option<Literal>) -> Result<TokenStream2> {
+    match value {
+        Some(Literal::Int(value)) => Ok(quote!(Some(#value))),
+        None => Ok(quote!(None)),
+        _ => Err(Error::new(
+            arg.ident.span(),
+            "Limits of i64 arguments must be integers",
+        )),
+    }
+}
+
+fn number_tokens(value: &Option<Literal>) -> TokenStream2 {
+    match value {
+        Some(Literal::Int(value)) => {
+            let value = *value as f64;
+            quote!(Some(#value))
+        }
+        Some(Literal::Float(value)) => quote!(Some(#value)),
+        _ => quote!(None),
+    }
+}
+
 // Parse arguments and get from ctx the values
 pub fn parse_arguments(sig: &mut Signature, block: &mut Block) -> Result<Vec<Argument>> {
     let mut arguments = Vec::new();
@@ -124,6 +259,10 @@ pub fn parse_arguments(sig: &mut Signature, block: &mut Block) -> Result<Vec<Arg
             CommandOptionType::Integer => format_ident!("get_integer"),
             CommandOptionType::Number => format_ident!("get_number"),
             CommandOptionType::String => format_ident!("get_string"),
+            CommandOptionType::Channel => format_ident!("get_channel"),
+            CommandOptionType::Role => format_ident!("get_role"),
+            CommandOptionType::Attachment => format_ident!("get_attachment"),
+            CommandOptionType::Mentionable => format_ident!("get_mentionable"),
             _ => unimplemented!(),
         };
 
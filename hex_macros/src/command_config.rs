@@ -0,0 +1,100 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{spanned::Spanned, Attribute, Error, Lit};
+
+use crate::{
+    attr::{self, Value},
+    common::Result,
+};
+
+/// The `CommandConfig` described by the `#[permissions(...)]`, `#[guild_only]`, `#[nsfw]`,
+/// `#[owner_only]` and `#[cooldown(...)]` attributes of a command
+pub fn config_tokens(attrs: &[Attribute]) -> Result<TokenStream2> {
+    let mut stream = quote!(CommandConfig::new());
+
+    for attr in attrs {
+        let parsed = attr::parse_attribute(attr)?;
+
+        if parsed.path.is_ident("permissions") {
+            let permissions = parsed
+                .values
+                .iter()
+                .map(|value| match value {
+                    Value::Ident(ident) => Ok(quote!(Permissions::#ident)),
+                    _ => Err(Error::new(
+                        value.span(),
+                        "Expected a permission, like MANAGE_GUILD",
+                    )),
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            if permissions.is_empty() {
+                return Err(Error::new(attr.span(), "Expected at least one permission"));
+            }
+
+            stream.extend(quote!(.set_permissions(#(#permissions)|*)));
+        } else if parsed.path.is_ident("guild_only") {
+            stream.extend(quote!(.set_guild_only(true)));
+        } else if parsed.path.is_ident("nsfw") {
+            stream.extend(quote!(.set_nsfw(true)));
+        } else if parsed.path.is_ident("owner_only") {
+            stream.extend(quote!(.set_owner_only(true)));
+        } else if parsed.path.is_ident("cooldown") {
+            let cooldown = cooldown_tokens(attr, &parsed.values)?;
+            stream.extend(quote!(.add_cooldown(#cooldown)));
+        }
+    }
+
+    Ok(stream)
+}
+
+/// A `Cooldown` from either `#[cooldown(seconds)]`, allowing each user one use every `seconds`,
+/// or `#[cooldown(bucket, uses, seconds)]`, where the bucket is `user`, `guild` or `global`
+fn cooldown_tokens(attr: &Attribute, values: &[Value]) -> Result<TokenStream2> {
+    let (bucket, uses, seconds): (_, u32, u64) = match values {
+        [seconds] => (quote!(CooldownBucket::User), 1, parse_int(seconds)?),
+        [Value::Ident(bucket), uses, seconds] => {
+            let bucket = match bucket.to_string().as_str() {
+                "user" => quote!(CooldownBucket::User),
+                "guild" => quote!(CooldownBucket::Guild),
+                "global" => quote!(CooldownBucket::Global),
+                _ => {
+                    return Err(Error::new(
+                        bucket.span(),
+                        "Expected user, guild or global as the bucket",
+                    ))
+                }
+            };
+
+            (bucket, parse_int(uses)?, parse_int(seconds)?)
+        }
+        _ => {
+            return Err(Error::new(
+                attr.span(),
+                "Expected #[cooldown(seconds)] or #[cooldown(bucket, uses, seconds)]",
+            ))
+        }
+    };
+
+    if uses == 0 || seconds == 0 {
+        return Err(Error::new(
+            attr.span(),
+            "Cooldown uses and seconds must be greater than zero",
+        ));
+    }
+
+    Ok(quote! {
+        Cooldown::new(#bucket, #uses, std::time::Duration::from_secs(#seconds))
+    })
+}
+
+fn parse_int<N>(value: &Value) -> Result<N>
+where
+    N: std::str::FromStr,
+    N::Err: std::fmt::Display,
+{
+    match value {
+        Value::Lit(Lit::Int(lit)) => lit.base10_parse::<N>(),
+        _ => Err(Error::new(value.span(), "Expected a positive integer")),
+    }
+}
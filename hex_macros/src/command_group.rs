@@ -0,0 +1,350 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{parse2, spanned::Spanned, Attribute, Error, Item, ItemFn, ItemMod, Lit};
+
+use crate::{
+    attr,
+    command::{option_tokens, parse_arguments},
+    command_config::config_tokens,
+    common::Result,
+    util::{self, capitalize},
+};
+
+/// A function of the group marked with `#[subcommand]`
+struct Subcommand {
+    name: String,
+    description: String,
+    fn_ident: syn::Ident,
+    options: TokenStream2,
+    block: syn::Block,
+    /// Arguments with `#[autocomplete]`, with the function suggesting their values
+    autocompletes: Vec<(String, syn::Ident)>,
+}
+
+/// A nested module marked with `#[subcommand_group]`
+struct SubcommandGroup {
+    name: String,
+    description: String,
+    module: ItemMod,
+    subcommands: Vec<Subcommand>,
+}
+
+pub fn command_group(attr: TokenStream2, input: TokenStream2) -> Result<TokenStream2> {
+    let module = parse2::<ItemMod>(input)?;
+    let description = parse2::<syn::LitStr>(attr)?;
+
+    let ItemMod {
+        attrs,
+        vis,
+        ident,
+        content,
+        ..
+    } = module;
+
+    let Some((_, items)) = content else {
+        return Err(Error::new(
+            ident.span(),
+            "Command groups must be inline modules",
+        ));
+    };
+
+    let command_name = get_name(&attrs, &ident);
+    let config_tokens = config_tokens(&attrs)?;
+
+    let mut subcommands = Vec::new();
+    let mut groups = Vec::new();
+    let mut other_items = Vec::new();
+    for item in items {
+        match item {
+            Item::Fn(fun) if has_attribute(&fun.attrs, "subcommand") => {
+                subcommands.push(parse_subcommand(fun)?)
+            }
+            Item::Mod(module) if has_attribute(&module.attrs, "subcommand_group") => {
+                groups.push(parse_group(module)?)
+            }
+            item => other_items.push(item),
+        }
+    }
+
+    if subcommands.is_empty() && groups.is_empty() {
+        return Err(Error::new(
+            ident.span(),
+            "Command groups need at least one #[subcommand] function or #[subcommand_group] module",
+        ));
+    }
+
+    let struct_name = format_ident!("{}Command", capitalize(&ident.to_string()));
+
+    let functions = subcommands.iter().map(subcommand_function);
+    let group_modules = groups.iter().map(|group| {
+        let ItemMod {
+            vis, ident, attrs, ..
+        } = &group.module;
+        let attrs = attrs
+            .iter()
+            .filter(|attr| !attr.path.is_ident("subcommand_group") && !attr.path.is_ident("name"));
+        let items = group
+            .module
+            .content
+            .as_ref()
+            .map(|(_, items)| items.clone())
+            .unwrap_or_default();
+        let functions = group.subcommands.iter().map(subcommand_function);
+
+        quote! {
+            #(#attrs)*
+            #vis mod #ident {
+                #(#items)*
+                #(#functions)*
+            }
+        }
+    });
+
+    let mut builders = subcommands
+        .iter()
+        .map(subcommand_builder)
+        .collect::<Vec<_>>();
+    builders.extend(groups.iter().map(|group| {
+        let SubcommandGroup {
+            name, description, ..
+        } = group;
+        let subcommands = group.subcommands.iter().map(subcommand_builder);
+
+        quote! {
+            CommandOptionBuilder::new(#name, #description, CommandOptionType::SubCommandGroup)
+                #(.add_option(#subcommands))*
+        }
+    }));
+
+    let mut routes = subcommands
+        .iter()
+        .map(|subcommand| {
+            let Subcommand { name, fn_ident, .. } = subcommand;
+            quote!((None, Some(#name)) => #fn_ident(ctx).await,)
+        })
+        .collect::<Vec<_>>();
+    for group in groups.iter() {
+        let group_name = &group.name;
+        let group_ident = &group.module.ident;
+
+        routes.extend(group.subcommands.iter().map(|subcommand| {
+            let Subcommand { name, fn_ident, .. } = subcommand;
+            quote!((Some(#group_name), Some(#name)) => #group_ident::#fn_ident(ctx).await,)
+        }));
+    }
+
+    let mut autocomplete_routes = subcommands
+        .iter()
+        .flat_map(|subcommand| {
+            let name = &subcommand.name;
+            subcommand.autocompletes.iter().map(move |(argument, fun)| {
+                quote!((None, Some(#name), #argument) => #fun(&ctx, value).await,)
+            })
+        })
+        .collect::<Vec<_>>();
+    for group in groups.iter() {
+        let group_name = &group.name;
+        let group_ident = &group.module.ident;
+
+        autocomplete_routes.extend(group.subcommands.iter().flat_map(|subcommand| {
+            let name = &subcommand.name;
+            subcommand.autocompletes.iter().map(move |(argument, fun)| {
+                quote! {
+                    (Some(#group_name), Some(#name), #argument) => {
+                        #group_ident::#fun(&ctx, value).await
+                    }
+                }
+            })
+        }));
+    }
+
+    let autocomplete_tokens = if autocomplete_routes.is_empty() {
+        quote!()
+    } else {
+        quote! {
+            async fn autocomplete(&self, ctx: CommandContext) -> anyhow::Result<Vec<CommandOptionChoice>> {
+                let Some((name, value)) = ctx.options().focused() else {
+                    return Ok(vec![]);
+                };
+
+                let group = ctx.options().subcommand_group();
+                let subcommand = ctx.options().subcommand();
+                match (group.as_deref(), subcommand.as_deref(), name.as_str()) {
+                    #(#autocomplete_routes)*
+                    _ => Ok(vec![]),
+                }
+            }
+        }
+    };
+
+    let expanded = quote! {
+        #vis mod #ident {
+            #(#other_items)*
+
+            #(#functions)*
+
+            #(#group_modules)*
+
+            pub struct #struct_name;
+
+            #[async_trait]
+            impl Command for #struct_name {
+                fn command_config(&self) -> CommandConfig {
+                    #config_tokens
+                }
+
+                fn build_command(&self, application_id: Id<ApplicationMarker>) -> CommandBuilder {
+                    CommandBuilder::new(application_id, #command_name, #description)
+                        .set_config(&self.command_config())
+                        #(.add_option(#builders))*
+                }
+
+                async fn run(&self, ctx: CommandContext) -> anyhow::Result<()> {
+                    let group = ctx.options().subcommand_group();
+                    let subcommand = ctx.options().subcommand();
+                    match (group.as_deref(), subcommand.as_deref()) {
+                        #(#routes)*
+                        _ => anyhow::bail!("Unknown subcommand: {:?} {:?}", group, subcommand),
+                    }
+                }
+
+                #autocomplete_tokens
+            }
+        }
+
+        #vis use self::#ident::#struct_name;
+    };
+
+    Ok(expanded)
+}
+
+fn has_attribute(attrs: &[Attribute], name: &str) -> bool {
+    attrs.iter().any(|attr| attr.path.is_ident(name))
+}
+
+/// The `#[name]` of an item, defaulting to its identifier
+fn get_name(attrs: &[Attribute], ident: &syn::Ident) -> String {
+    util::get_attribute_argument_literal(attrs, "name")
+        .map(|lit| match lit {
+            Lit::Str(str) => str.value(),
+            _ => ident.to_string(),
+        })
+        .unwrap_or(ident.to_string())
+}
+
+/// The description given to the `attribute` attribute, like `#[subcommand("description")]`
+fn get_description(attrs: &[Attribute], attribute: &str) -> Result<String> {
+    Ok(attrs
+        .iter()
+        .find(|attr| attr.path.is_ident(attribute))
+        .map(attr::parse_attribute)
+        .transpose()?
+        .map(|attr| attr.parse_string())
+        .transpose()?
+        .unwrap_or_default())
+}
+
+fn subcommand_function(subcommand: &Subcommand) -> TokenStream2 {
+    let Subcommand {
+        fn_ident, block, ..
+    } = subcommand;
+
+    quote! {
+        pub(super) async fn #fn_ident(mut ctx: CommandContext) -> anyhow::Result<()> {
+            #block
+        }
+    }
+}
+
+fn subcommand_builder(subcommand: &Subcommand) -> TokenStream2 {
+    let Subcommand {
+        name,
+        description,
+        options,
+        ..
+    } = subcommand;
+
+    quote! {
+        CommandOptionBuilder::new(#name, #description, CommandOptionType::SubCommand)
+            #options
+    }
+}
+
+fn parse_subcommand(fun: ItemFn) -> Result<Subcommand> {
+    let ItemFn {
+        attrs,
+        mut sig,
+        mut block,
+        ..
+    } = fun;
+
+    if sig.inputs.is_empty() {
+        return Err(Error::new(
+            sig.inputs.span(),
+            "Expected at least CommandContext as a parameter",
+        ));
+    }
+
+    let description = get_description(&attrs, "subcommand")?;
+    let name = get_name(&attrs, &sig.ident);
+    let args = parse_arguments(&mut sig, &mut block)?;
+    let autocompletes = args
+        .iter()
+        .filter_map(|arg| Some((arg.name.clone(), arg.autocomplete.clone()?)))
+        .collect();
+
+    Ok(Subcommand {
+        name,
+        description,
+        fn_ident: sig.ident,
+        options: option_tokens(&args)?,
+        block: *block,
+        autocompletes,
+    })
+}
+
+fn parse_group(mut module: ItemMod) -> Result<SubcommandGroup> {
+    let Some((_, items)) = module.content.take() else {
+        return Err(Error::new(
+            module.ident.span(),
+            "Subcommand groups must be inline modules",
+        ));
+    };
+
+    let description = get_description(&module.attrs, "subcommand_group")?;
+    let name = get_name(&module.attrs, &module.ident);
+
+    let mut subcommands = Vec::new();
+    let mut other_items = Vec::new();
+    for item in items {
+        match item {
+            Item::Fn(fun) if has_attribute(&fun.attrs, "subcommand") => {
+                subcommands.push(parse_subcommand(fun)?)
+            }
+            Item::Mod(nested) if has_attribute(&nested.attrs, "subcommand_group") => {
+                // Discord only allows one level of groups
+                return Err(Error::new(
+                    nested.ident.span(),
+                    "Subcommand groups can't be nested",
+                ));
+            }
+            item => other_items.push(item),
+        }
+    }
+
+    if subcommands.is_empty() {
+        return Err(Error::new(
+            module.ident.span(),
+            "Subcommand groups need at least one #[subcommand] function",
+        ));
+    }
+
+    module.content = Some((Default::default(), other_items));
+
+    Ok(SubcommandGroup {
+        name,
+        description,
+        module,
+        subcommands,
+    })
+}
@@ -1,6 +1,8 @@
 mod argument;
 mod attr;
 mod command;
+mod command_config;
+mod command_group;
 mod common;
 mod util;
 
@@ -21,6 +23,15 @@ pub fn command(attrs: TokenStream, input: TokenStream) -> TokenStream {
     extract(command::command(attrs.into(), input.into()))
 }
 
+/// Declares a slash command whose subcommands are the `#[subcommand("description")]`
+/// functions of an inline module. Nested inline modules marked with
+/// `#[subcommand_group("description")]` become subcommand groups, routed by group and name.
+/// The modules must bring the framework prelude into scope.
+#[proc_macro_attribute]
+pub fn command_group(attrs: TokenStream, input: TokenStream) -> TokenStream {
+    extract(command_group::command_group(attrs.into(), input.into()))
+}
+
 use quote::quote;
 use syn::{parse_macro_input, Data, DeriveInput};
 
@@ -75,7 +75,7 @@ pub fn is_optional(ty: &Type) -> bool {
             .path
             .segments
             .first()
-            .map_or(false, |segment| segment.ident == "Option"),
+            .is_some_and(|segment| segment.ident == "Option"),
         _ => false,
     }
 }
@@ -102,6 +102,10 @@ impl From<Type> for OptionTypeWrapper {
             "f64" => Self(CommandOptionType::Number),
             "bool" => Self(CommandOptionType::Boolean),
             "User" => Self(CommandOptionType::User),
+            "InteractionChannel" => Self(CommandOptionType::Channel),
+            "Role" => Self(CommandOptionType::Role),
+            "Attachment" => Self(CommandOptionType::Attachment),
+            "Mentionable" => Self(CommandOptionType::Mentionable),
             _ => panic!("Unexpected type: {}", segment.ident),
         }
     }
@@ -115,6 +119,10 @@ impl ToTokens for OptionTypeWrapper {
             CommandOptionType::Number => format_ident!("Number"),
             CommandOptionType::Boolean => format_ident!("Boolean"),
             CommandOptionType::User => format_ident!("User"),
+            CommandOptionType::Channel => format_ident!("Channel"),
+            CommandOptionType::Role => format_ident!("Role"),
+            CommandOptionType::Attachment => format_ident!("Attachment"),
+            CommandOptionType::Mentionable => format_ident!("Mentionable"),
             _ => unimplemented!(),
         };
 
@@ -7,6 +7,7 @@ use hex_discord::{
     twilight_http::client::InteractionClient,
     twilight_model::{
         gateway::payload::incoming::InteractionCreate,
+        guild::Permissions,
         id::{
             marker::{ApplicationMarker, GuildMarker},
             Id,
@@ -14,13 +15,17 @@ use hex_discord::{
     },
     ApiCommand, InteractionData,
 };
-use hex_framework::{watcher::Watcher, CommandBuilder, CommandContext, HexClient};
+use hex_framework::{
+    format_wait, watcher::Watcher, CommandBuilder, CommandConfig, CommandContext, Cooldowns,
+    HexClient, Response,
+};
 
 pub async fn execute_command(
     interaction: Box<InteractionCreate>,
     client: Arc<HexClient>,
     watcher: Arc<Watcher>,
     database: Arc<HexDatabase>,
+    cooldowns: Arc<Cooldowns>,
 ) -> anyhow::Result<()> {
     let data = interaction
         .data
@@ -38,22 +43,119 @@ pub async fn execute_command(
         database,
         data.options,
     );
-    if ctx.interaction.is_dm() {
-        ctx.reply("você só pode usar Hex em um servidor.").await?;
-        return Ok(());
-    }
     let command = COMMANDS
         .get(data.name.as_str())
         .ok_or(anyhow::anyhow!("Command not found"))?;
 
-    let result = command.run(ctx).await;
-    if result.is_err() {
-        eprintln!("{}", result.unwrap_err());
+    if let Some(error) = config_error(&data.name, &command.command_config(), &ctx, &cooldowns) {
+        ctx.reply(Response::from(error).error_response().set_ephemeral())
+            .await?;
+        return Ok(());
+    }
+
+    if let Err(e) = command.run(ctx).await {
+        eprintln!("{}", e);
     }
 
     Ok(())
 }
 
+/// Why the author can't use the command right now, checking the requirements of its config
+fn config_error(
+    name: &str,
+    config: &CommandConfig,
+    ctx: &CommandContext,
+    cooldowns: &Cooldowns,
+) -> Option<String> {
+    let interaction = &ctx.interaction;
+
+    if config.guild_only && interaction.is_dm() {
+        return Some("você só pode usar este comando em um servidor.".to_string());
+    }
+
+    if config.owner_only
+        && !interaction
+            .author_id()
+            .is_some_and(|id| ctx.client.owner_ids.contains(&id))
+    {
+        return Some("apenas os donos do Hex podem usar este comando.".to_string());
+    }
+
+    if let (Some(required), Some(member)) = (config.permissions, &interaction.member) {
+        let allowed = member.permissions.is_some_and(|permissions| {
+            permissions.contains(required) || permissions.contains(Permissions::ADMINISTRATOR)
+        });
+
+        if !allowed {
+            return Some("você não tem permissão para usar este comando.".to_string());
+        }
+    }
+
+    if config.nsfw
+        && !interaction
+            .channel
+            .as_ref()
+            .and_then(|channel| channel.nsfw)
+            .unwrap_or(false)
+    {
+        return Some("este comando só pode ser usado em canais NSFW.".to_string());
+    }
+
+    // Checked last so requests that fail the other requirements don't spend uses
+    if let Some(user_id) = interaction.author_id() {
+        let wait = cooldowns.try_use(name, &config.cooldowns, user_id, interaction.guild_id);
+
+        if let Some(wait) = wait {
+            return Some(format!(
+                "aguarde {} para usar este comando novamente.",
+                format_wait(wait)
+            ));
+        }
+    }
+
+    None
+}
+
+/// Answers an autocomplete interaction with the suggestions of the command
+pub async fn execute_autocomplete(
+    interaction: Box<InteractionCreate>,
+    client: Arc<HexClient>,
+    watcher: Arc<Watcher>,
+    database: Arc<HexDatabase>,
+) -> anyhow::Result<()> {
+    let data = interaction
+        .data
+        .clone()
+        .and_then(|d| match d {
+            InteractionData::ApplicationCommand(data) => Some(data),
+            _ => None,
+        })
+        .ok_or(anyhow::anyhow!("Data not found"))?;
+
+    let command = COMMANDS
+        .get(data.name.as_str())
+        .ok_or(anyhow::anyhow!("Command not found"))?;
+
+    let mut ctx = CommandContext::new(
+        client,
+        Box::new(interaction.0),
+        watcher,
+        database,
+        data.options,
+    );
+
+    // Failed suggestions still need an answer, or Discord keeps the user waiting
+    let choices = match command.autocomplete(ctx.clone()).await {
+        Ok(choices) => choices,
+        Err(e) => {
+            eprintln!("{}", e);
+            vec![]
+        }
+    };
+
+    ctx.reply_autocomplete(choices).await
+}
+
 pub async fn register_commands(application_id: Id<ApplicationMarker>, client: Arc<HexClient>) {
     let commands: Vec<CommandBuilder> = {
         let mut commands = Vec::new();
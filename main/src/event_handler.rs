@@ -1,25 +1,36 @@
 use std::sync::Arc;
 
 use crate::command_handler;
+use hex_commands::mention::handle_mention;
 use hex_database::HexDatabase;
 use hex_discord::{
     twilight_gateway::Event,
-    twilight_model::gateway::payload::incoming::{InteractionCreate, MessageCreate, Ready},
+    twilight_model::{
+        application::interaction::InteractionType,
+        gateway::payload::incoming::{InteractionCreate, MessageCreate, Ready},
+    },
 };
-use hex_framework::{watcher::Watcher, HexClient};
+use hex_framework::{watcher::Watcher, Cooldowns, HexClient};
 
 pub struct EventHandler {
     client: Arc<HexClient>,
     watcher: Arc<Watcher>,
     database: Arc<HexDatabase>,
+    cooldowns: Arc<Cooldowns>,
 }
 
 impl EventHandler {
-    pub fn new(client: Arc<HexClient>, watcher: Arc<Watcher>, database: Arc<HexDatabase>) -> Self {
+    pub fn new(
+        client: Arc<HexClient>,
+        watcher: Arc<Watcher>,
+        database: Arc<HexDatabase>,
+        cooldowns: Arc<Cooldowns>,
+    ) -> Self {
         Self {
             client,
             watcher,
             database,
+            cooldowns,
         }
     }
 
@@ -53,11 +64,43 @@ impl EventHandler {
         self,
         interaction: Box<InteractionCreate>,
     ) -> anyhow::Result<()> {
-        command_handler::execute_command(interaction, self.client, self.watcher, self.database)
-            .await
+        match interaction.kind {
+            InteractionType::ApplicationCommandAutocomplete => {
+                command_handler::execute_autocomplete(
+                    interaction,
+                    self.client,
+                    self.watcher,
+                    self.database,
+                )
+                .await
+            }
+            _ => {
+                command_handler::execute_command(
+                    interaction,
+                    self.client,
+                    self.watcher,
+                    self.database,
+                    self.cooldowns,
+                )
+                .await
+            }
+        }
     }
 
-    pub async fn message_create(self, _message: Box<MessageCreate>) -> anyhow::Result<()> {
-        Ok(())
+    pub async fn message_create(self, message: Box<MessageCreate>) -> anyhow::Result<()> {
+        let result = handle_mention(
+            self.client,
+            self.watcher,
+            self.database,
+            self.cooldowns,
+            message.0,
+        )
+        .await;
+
+        if let Err(e) = &result {
+            eprintln!("{}", e);
+        }
+
+        result
     }
 }
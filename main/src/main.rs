@@ -12,7 +12,7 @@ use hex_discord::twilight_gateway::{
     Config, Intents,
 };
 
-use hex_framework::{watcher::Watcher, HexClient};
+use hex_framework::{watcher::Watcher, Cooldowns, HexClient};
 use tokio_stream::StreamExt;
 
 #[tokio::main]
@@ -42,6 +42,7 @@ async fn main() {
     );
     let client = Arc::new(HexClient::new(discord_token).await.unwrap());
     let watcher = Arc::new(Watcher::new());
+    let cooldowns = Arc::new(Cooldowns::new());
 
     if config::DEBUG {
         println!("=== DEBUG ===");
@@ -66,7 +67,12 @@ async fn main() {
             }
         };
 
-        let event_handler = EventHandler::new(client.clone(), watcher.clone(), database.clone());
+        let event_handler = EventHandler::new(
+            client.clone(),
+            watcher.clone(),
+            database.clone(),
+            cooldowns.clone(),
+        );
         tokio::spawn(event_handler.handle(event));
     }
 }